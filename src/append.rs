@@ -0,0 +1,149 @@
+use crate::{DatasetOptions, DotthzFile};
+use hdf5::{Dataset, H5Type};
+use ndarray::{s, ArrayView2};
+use std::error::Error;
+use std::marker::PhantomData;
+
+/// Number of rows appended between flushes unless set with [`Appender::set_flush_interval`].
+const DEFAULT_FLUSH_INTERVAL: usize = 16;
+
+/// Rows assumed when choosing the chunk shape of an extendable dataset.
+const AUTO_CHUNK_ROWS: usize = 1024;
+
+/// Appends rows to an extendable dataset, one trace at a time.
+///
+/// The dataset has an unlimited first dimension and grows by one row per trace. The file is
+/// flushed every few rows and when the appender is dropped, so a crash during acquisition leaves
+/// a readable file with all completed traces. To follow the dotThz layout of time-domain traces,
+/// append the time axis as the first row.
+pub struct Appender<T> {
+    dataset: Dataset,
+    rows: usize,
+    row_len: usize,
+    flush_interval: usize,
+    unflushed: usize,
+    element: PhantomData<T>,
+}
+
+impl DotthzFile {
+    /// Create an empty dataset of shape `(0, row_len)` whose first dimension can grow, and return
+    /// an [`Appender`] to add rows to it.
+    ///
+    /// The compression options are applied as in [`DotthzFile::add_dataset_with`], the default
+    /// chunk shape holds up to about 1 MiB of whole rows.
+    pub fn create_appendable_dataset<T: H5Type>(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        row_len: usize,
+        options: &DatasetOptions,
+    ) -> Result<Appender<T>, Box<dyn Error>> {
+        options.validate()?;
+        let chunk = options.chunk.clone().unwrap_or_else(|| {
+            DatasetOptions::auto_chunk(&[AUTO_CHUNK_ROWS, row_len], std::mem::size_of::<T>())
+        });
+        let dataset = self
+            .get_group(group_name)?
+            .new_dataset::<T>()
+            .shape((0.., row_len))
+            .chunk(chunk)
+            .set_filters(&options.filters())
+            .create(dataset_name)?;
+        Ok(Appender::new(dataset))
+    }
+
+    /// Continue appending to an existing extendable dataset, e.g. after a restart.
+    pub fn open_appender<T: H5Type>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> Result<Appender<T>, Box<dyn Error>> {
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        if dataset.ndim() != 2 || !dataset.is_resizable() {
+            return Err(
+                format!("dataset `{}` is not an extendable 2D dataset", dataset_name).into(),
+            );
+        }
+        Ok(Appender::new(dataset))
+    }
+}
+
+impl<T: H5Type> Appender<T> {
+    fn new(dataset: Dataset) -> Self {
+        let shape = dataset.shape();
+        Appender {
+            rows: shape[0],
+            row_len: shape[1],
+            dataset,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            unflushed: 0,
+            element: PhantomData,
+        }
+    }
+
+    /// Append a single row.
+    pub fn append(&mut self, row: &[T]) -> Result<(), Box<dyn Error>> {
+        self.append_rows(ArrayView2::from_shape((1, row.len()), row)?)
+    }
+
+    /// Append several rows at once.
+    pub fn append_rows(&mut self, rows: ArrayView2<'_, T>) -> Result<(), Box<dyn Error>> {
+        if rows.ncols() != self.row_len {
+            return Err(format!(
+                "rows of length {} cannot be appended to `{}` with rows of length {}",
+                rows.ncols(),
+                self.dataset.name(),
+                self.row_len
+            )
+            .into());
+        }
+        let end = self.rows + rows.nrows();
+        self.dataset.resize((end, self.row_len))?;
+        self.dataset.write_slice(rows, s![self.rows..end, ..])?;
+        self.rows = end;
+
+        self.unflushed += rows.nrows();
+        if self.unflushed >= self.flush_interval {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush the file after every `rows` appended rows, `1` flushes after every row.
+    pub fn set_flush_interval(&mut self, rows: usize) {
+        self.flush_interval = rows.max(1);
+    }
+
+    /// Write all appended rows to disk.
+    pub fn flush(&mut self) -> hdf5::Result<()> {
+        self.dataset.file()?.flush()?;
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    /// Number of rows in the dataset.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns `true` if no rows have been appended.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// The dataset rows are appended to.
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+}
+
+impl<T> Drop for Appender<T> {
+    fn drop(&mut self) {
+        if self.unflushed > 0 {
+            if let Ok(file) = self.dataset.file() {
+                // errors cannot be reported from drop, call `flush` to handle them
+                let _ = file.flush();
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::DotthzFile;
+use hdf5::{Dataset, Datatype, H5Type};
+use ndarray::ArrayD;
+use num_complex::Complex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// Complex value in the compound layout of h5py, see [`ComplexLayout::Native`].
+///
+/// [`ComplexLayout::Native`]: crate::ComplexLayout::Native
+#[derive(H5Type, Clone, Copy)]
+#[repr(C)]
+struct NativeComplex<T: H5Type + Copy> {
+    r: T,
+    i: T,
+}
+
+/// An n-dimensional array tagged with its element type, shared by the export formats.
+///
+/// Datasets keep their element type for `f32`, `f64`, `i32` and `i64`, other types are
+/// converted to `f64` when read. Complex datasets in the native layout are read as `c32` or
+/// `c64`, also without the `hdf5-complex` feature, and written with
+/// [`DotthzFile::add_complex_dataset`]. Interleaved complex datasets are real datasets with a
+/// trailing dimension of 2 and read as such.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "dtype", content = "values", rename_all = "lowercase")
+)]
+pub enum TypedArray {
+    /// 32 bit floating point values.
+    F32(ArrayD<f32>),
+    /// 64 bit floating point values.
+    F64(ArrayD<f64>),
+    /// 32 bit integer values.
+    I32(ArrayD<i32>),
+    /// 64 bit integer values.
+    I64(ArrayD<i64>),
+    /// Complex values with 32 bit real and imaginary part.
+    C32(ArrayD<Complex<f32>>),
+    /// Complex values with 64 bit real and imaginary part.
+    C64(ArrayD<Complex<f64>>),
+}
+
+impl TypedArray {
+    /// Read a whole dataset with the element type it is stored as.
+    pub fn read(dataset: &Dataset) -> hdf5::Result<Self> {
+        let dtype = dataset.dtype()?;
+        Ok(if dtype.is::<f32>() {
+            TypedArray::F32(dataset.read_dyn()?)
+        } else if dtype.is::<i32>() {
+            TypedArray::I32(dataset.read_dyn()?)
+        } else if dtype.is::<i64>() {
+            TypedArray::I64(dataset.read_dyn()?)
+        } else if dtype.is::<NativeComplex<f32>>() {
+            TypedArray::C32(read_native_complex(dataset)?)
+        } else if dtype.is::<NativeComplex<f64>>() {
+            TypedArray::C64(read_native_complex(dataset)?)
+        } else {
+            TypedArray::F64(dataset.read_dyn()?)
+        })
+    }
+
+    /// Add the array as a new dataset of an existing group.
+    pub fn write_into(
+        &self,
+        file: &mut DotthzFile,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        match self {
+            TypedArray::F32(data) => file.add_dataset(group_name, dataset_name, data.view()),
+            TypedArray::F64(data) => file.add_dataset(group_name, dataset_name, data.view()),
+            TypedArray::I32(data) => file.add_dataset(group_name, dataset_name, data.view()),
+            TypedArray::I64(data) => file.add_dataset(group_name, dataset_name, data.view()),
+            TypedArray::C32(data) => {
+                file.add_complex_dataset(group_name, dataset_name, data.view())
+            }
+            TypedArray::C64(data) => {
+                file.add_complex_dataset(group_name, dataset_name, data.view())
+            }
+        }
+    }
+
+    /// The shape of the array.
+    pub fn shape(&self) -> &[usize] {
+        match self {
+            TypedArray::F32(data) => data.shape(),
+            TypedArray::F64(data) => data.shape(),
+            TypedArray::I32(data) => data.shape(),
+            TypedArray::I64(data) => data.shape(),
+            TypedArray::C32(data) => data.shape(),
+            TypedArray::C64(data) => data.shape(),
+        }
+    }
+
+    /// Number of values.
+    pub fn len(&self) -> usize {
+        self.shape().iter().product()
+    }
+
+    /// Returns `true` if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Whether a datatype is the compound type of native complex datasets.
+pub(crate) fn is_native_complex(dtype: &Datatype) -> bool {
+    dtype.is::<NativeComplex<f32>>() || dtype.is::<NativeComplex<f64>>()
+}
+
+/// Read a native complex dataset, independent of the `hdf5-complex` feature.
+fn read_native_complex<T: H5Type + Copy>(dataset: &Dataset) -> hdf5::Result<ArrayD<Complex<T>>> {
+    Ok(dataset
+        .read_dyn::<NativeComplex<T>>()?
+        .mapv(|c| Complex::new(c.r, c.i)))
+}
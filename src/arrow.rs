@@ -0,0 +1,260 @@
+use crate::array::{is_native_complex, TypedArray};
+use crate::{collect_thz_files, DotthzFile, DotthzMetaData};
+use arrow_array::builder::{
+    Float64Builder, ListBuilder, StringBuilder, UInt32Builder, UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use indexmap::IndexSet;
+use ndarray::Axis;
+use parquet::arrow::ArrowWriter;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Columns holding the fixed meta-data fields, `md` columns are renamed if they collide.
+const META_DATA_COLUMNS: [&str; 11] = [
+    "description",
+    "user",
+    "email",
+    "orcid",
+    "institution",
+    "instrument",
+    "mode",
+    "date",
+    "time",
+    "version",
+    "ds_description",
+];
+
+/// A single trace of a dataset together with the meta-data of its group.
+struct TraceRow {
+    file: String,
+    group: String,
+    dataset: String,
+    trace: u32,
+    ds_description: Option<String>,
+    meta_data: Arc<DotthzMetaData>,
+    shape: Vec<u64>,
+    axis: Option<Vec<f64>>,
+    signal: Vec<f64>,
+    imag: Option<Vec<f64>>,
+}
+
+/// Flatten a `DotthzFile` into an Arrow record batch with one row per trace.
+///
+/// A 2D dataset of shape `(n, samples)` holds the time axis in its first row and `n - 1`
+/// traces in the remaining rows, each trace becomes one row with list-typed `time_axis` and
+/// `signal` columns. 1D datasets become a single row without `time_axis`, higher dimensional
+/// datasets a single row with the flattened data in `signal`.
+///
+/// The columns are `file`, `group`, `dataset`, `trace`, the meta-data fields `description`,
+/// `user`, `email`, `orcid`, `institution`, `instrument`, `mode`, `date`, `time`, `version` and
+/// `ds_description` (the description of the dataset), one column per `md` key (prefixed with
+/// `md:` if it collides with another column), followed by `shape`, `time_axis`, `signal` and
+/// `signal_imag`. Native complex datasets become a single row with the real part in `signal`,
+/// the imaginary part in `signal_imag` and, for 1D datasets, the attached axis in `time_axis`.
+/// Nested measurement groups are included with their full path in `group` and the meta-data
+/// they inherit, see [`DotthzFile::get_inherited_meta_data`].
+pub fn to_record_batch(file: &DotthzFile, source: &str) -> Result<RecordBatch, Box<dyn Error>> {
+    record_batch(&trace_rows(file, source)?)
+}
+
+/// Flatten several files into a single record batch, see [`to_record_batch`].
+///
+/// Directories are searched recursively for `.thz` files, see [`collect_thz_files`].
+pub fn files_to_record_batch<P: AsRef<Path>>(paths: &[P]) -> Result<RecordBatch, Box<dyn Error>> {
+    let mut rows = Vec::new();
+    for path in collect_thz_files(paths, true)? {
+        let file = DotthzFile::open(&path)?;
+        rows.extend(trace_rows(&file, &path.to_string_lossy())?);
+    }
+    record_batch(&rows)
+}
+
+/// Write a record batch to a Parquet file.
+pub fn write_parquet<P: AsRef<Path>>(batch: &RecordBatch, path: P) -> Result<(), Box<dyn Error>> {
+    let mut writer = ArrowWriter::try_new(fs::File::create(path)?, batch.schema(), None)?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Flatten files and directories of files into a Parquet table, see [`files_to_record_batch`].
+pub fn export_parquet<P: AsRef<Path>, Q: AsRef<Path>>(
+    paths: &[P],
+    output: Q,
+) -> Result<(), Box<dyn Error>> {
+    write_parquet(&files_to_record_batch(paths)?, output)
+}
+
+fn trace_rows(file: &DotthzFile, source: &str) -> Result<Vec<TraceRow>, Box<dyn Error>> {
+    let mut rows = Vec::new();
+    for group_name in file.get_measurement_group_names()? {
+        let meta_data = Arc::new(file.get_inherited_meta_data(&group_name)?);
+        for dataset in file.get_datasets(&group_name)? {
+            let name = dataset.name();
+            let dataset_name = name.rsplit('/').next().unwrap_or(&name).to_string();
+            let shape = dataset
+                .shape()
+                .iter()
+                .map(|&s| s as u64)
+                .collect::<Vec<u64>>();
+            let row = |trace: u32, axis: Option<Vec<f64>>, signal: Vec<f64>| TraceRow {
+                file: source.to_string(),
+                group: group_name.clone(),
+                dataset: dataset_name.clone(),
+                trace,
+                ds_description: meta_data
+                    .dataset_description(&dataset_name)
+                    .map(String::from),
+                meta_data: meta_data.clone(),
+                shape: shape.clone(),
+                axis,
+                signal,
+                imag: None,
+            };
+
+            if is_native_complex(&dataset.dtype()?) {
+                let values = match TypedArray::read(&dataset)? {
+                    TypedArray::C32(data) => {
+                        data.iter().map(|c| (c.re as f64, c.im as f64)).collect()
+                    }
+                    TypedArray::C64(data) => data.iter().map(|c| (c.re, c.im)).collect(),
+                    _ => Vec::new(),
+                };
+                let (signal, imag): (Vec<f64>, Vec<f64>) = values.into_iter().unzip();
+                let axis = match file.get_axis(&group_name, &dataset_name, 0)? {
+                    Some(axis) if shape.len() == 1 => Some(axis.read_raw::<f64>()?),
+                    _ => None,
+                };
+                rows.push(TraceRow {
+                    imag: Some(imag),
+                    ..row(0, axis, signal)
+                });
+                continue;
+            }
+
+            let data = dataset.read_dyn::<f64>()?;
+            if data.ndim() == 2 && data.shape()[0] >= 2 {
+                let axis = data
+                    .index_axis(Axis(0), 0)
+                    .iter()
+                    .copied()
+                    .collect::<Vec<f64>>();
+                for (i, trace) in data.outer_iter().skip(1).enumerate() {
+                    let signal = trace.iter().copied().collect();
+                    rows.push(row(i as u32, Some(axis.clone()), signal));
+                }
+            } else {
+                rows.push(row(0, None, data.iter().copied().collect()));
+            }
+        }
+    }
+    Ok(rows)
+}
+
+fn record_batch(rows: &[TraceRow]) -> Result<RecordBatch, Box<dyn Error>> {
+    let string_column = |value: &dyn Fn(&TraceRow) -> Option<&str>| -> ArrayRef {
+        let mut builder = StringBuilder::new();
+        for row in rows {
+            builder.append_option(value(row));
+        }
+        Arc::new(builder.finish())
+    };
+    let list_column = |value: &dyn Fn(&TraceRow) -> Option<&[f64]>| -> ArrayRef {
+        let mut builder = ListBuilder::new(Float64Builder::new());
+        for row in rows {
+            match value(row) {
+                Some(values) => {
+                    builder.values().append_slice(values);
+                    builder.append(true);
+                }
+                None => builder.append(false),
+            }
+        }
+        Arc::new(builder.finish())
+    };
+
+    let mut trace = UInt32Builder::new();
+    let mut shape = ListBuilder::new(UInt64Builder::new());
+    for row in rows {
+        trace.append_value(row.trace);
+        shape.values().append_slice(&row.shape);
+        shape.append(true);
+    }
+
+    let mut columns: Vec<(String, ArrayRef)> = vec![
+        (
+            "file".to_string(),
+            string_column(&|r| Some(r.file.as_str())),
+        ),
+        (
+            "group".to_string(),
+            string_column(&|r| Some(r.group.as_str())),
+        ),
+        (
+            "dataset".to_string(),
+            string_column(&|r| Some(r.dataset.as_str())),
+        ),
+        ("trace".to_string(), Arc::new(trace.finish())),
+    ];
+    for field in META_DATA_COLUMNS {
+        let column = string_column(&|r| {
+            let m = &r.meta_data;
+            let value = match field {
+                "description" => &m.description,
+                "user" => &m.user,
+                "email" => &m.email,
+                "orcid" => &m.orcid,
+                "institution" => &m.institution,
+                "instrument" => &m.instrument,
+                "mode" => &m.mode,
+                "date" => &m.date,
+                "time" => &m.time,
+                "version" => &m.version,
+                _ => return r.ds_description.as_deref(),
+            };
+            Some(value.as_str())
+        });
+        columns.push((field.to_string(), column));
+    }
+
+    let md_keys = rows
+        .iter()
+        .flat_map(|r| r.meta_data.md.keys())
+        .collect::<IndexSet<&String>>();
+    for key in md_keys {
+        let column = string_column(&|r| r.meta_data.md.get(key).map(String::as_str));
+        let name = if META_DATA_COLUMNS.contains(&key.as_str())
+            || [
+                "file",
+                "group",
+                "dataset",
+                "trace",
+                "shape",
+                "time_axis",
+                "signal",
+                "signal_imag",
+            ]
+            .contains(&key.as_str())
+        {
+            format!("md:{}", key)
+        } else {
+            key.clone()
+        };
+        columns.push((name, column));
+    }
+
+    columns.push(("shape".to_string(), Arc::new(shape.finish())));
+    columns.push(("time_axis".to_string(), list_column(&|r| r.axis.as_deref())));
+    columns.push((
+        "signal".to_string(),
+        list_column(&|r| Some(r.signal.as_slice())),
+    ));
+    columns.push((
+        "signal_imag".to_string(),
+        list_column(&|r| r.imag.as_deref()),
+    ));
+    Ok(RecordBatch::try_from_iter(columns)?)
+}
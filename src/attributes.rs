@@ -0,0 +1,264 @@
+use crate::DotthzFile;
+use hdf5::types::VarLenUnicode;
+use hdf5::{Dataset, Location};
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::str::FromStr;
+
+/// Attribute names of the fields of [`DatasetAttributes`].
+const UNIT: &str = "unit";
+const LABEL: &str = "label";
+const SAMPLING_INTERVAL: &str = "samplingInterval";
+const OFFSET: &str = "add_offset";
+const SCALE: &str = "scale_factor";
+
+/// Attributes written by other tools for the same purpose, read as fallback.
+const UNIT_ALIASES: [&str; 1] = ["units"];
+const LABEL_ALIASES: [&str; 1] = ["long_name"];
+const OFFSET_ALIASES: [&str; 1] = ["offset"];
+const SCALE_ALIASES: [&str; 1] = ["scale"];
+
+/// Attributes with a meaning of their own, never read as free-form attributes.
+const RESERVED: [&str; 13] = [
+    UNIT,
+    LABEL,
+    SAMPLING_INTERVAL,
+    OFFSET,
+    SCALE,
+    "units",
+    "long_name",
+    "offset",
+    "scale",
+    "CLASS",
+    "NAME",
+    "DIMENSION_LIST",
+    "REFERENCE_LIST",
+];
+
+/// Units recognised by [`si_factor`], with their factor to the SI base unit.
+const UNITS: [(&str, f64); 24] = [
+    ("fs", 1e-15),
+    ("ps", 1e-12),
+    ("ns", 1e-9),
+    ("us", 1e-6),
+    ("µs", 1e-6),
+    ("ms", 1e-3),
+    ("s", 1.0),
+    ("THz", 1e12),
+    ("GHz", 1e9),
+    ("MHz", 1e6),
+    ("Hz", 1.0),
+    ("V", 1.0),
+    ("mV", 1e-3),
+    ("uV", 1e-6),
+    ("µV", 1e-6),
+    ("nV", 1e-9),
+    ("A", 1.0),
+    ("mA", 1e-3),
+    ("uA", 1e-6),
+    ("µA", 1e-6),
+    ("nA", 1e-9),
+    ("pA", 1e-12),
+    ("mm", 1e-3),
+    ("um", 1e-6),
+];
+
+/// Units, axis labels and scaling of a dataset, stored as attributes of the dataset.
+///
+/// Units and labels are given per row, so a time-domain trace of shape `(2, N)` has the unit of
+/// its time axis first and the unit of its signal second.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DatasetAttributes {
+    /// Unit of each row, e.g. `["ps", "nA"]`.
+    pub units: Vec<String>,
+
+    /// Label of each row, e.g. `["Time", "Signal"]`.
+    pub labels: Vec<String>,
+
+    /// Spacing of the samples, in the unit of the first row.
+    pub sampling_interval: Option<f64>,
+
+    /// Offset to add to the scaled values, stored as `add_offset` as in the CF conventions.
+    pub offset: Option<f64>,
+
+    /// Factor to multiply the stored values with, stored as `scale_factor`.
+    pub scale: Option<f64>,
+
+    /// Additional attributes stored as key-value pairs.
+    pub extra: IndexMap<String, String>,
+}
+
+impl DatasetAttributes {
+    /// Attributes of a time-domain trace with a time axis and a signal row.
+    pub fn time_domain(time_unit: &str, signal_unit: &str) -> Self {
+        DatasetAttributes {
+            units: vec![time_unit.to_string(), signal_unit.to_string()],
+            labels: vec!["Time".to_string(), "Signal".to_string()],
+            ..Default::default()
+        }
+    }
+
+    /// Unit of a row, if set.
+    pub fn unit(&self, row: usize) -> Option<&str> {
+        self.units
+            .get(row)
+            .map(String::as_str)
+            .filter(|u| !u.is_empty())
+    }
+
+    /// Label of a row, if set.
+    pub fn label(&self, row: usize) -> Option<&str> {
+        self.labels
+            .get(row)
+            .map(String::as_str)
+            .filter(|l| !l.is_empty())
+    }
+
+    /// Returns `true` if no attribute is set.
+    pub fn is_empty(&self) -> bool {
+        *self == DatasetAttributes::default()
+    }
+}
+
+/// Factor converting a recognised unit, such as `ps`, `THz` or `nA`, to its SI base unit.
+pub fn si_factor(unit: &str) -> Option<f64> {
+    let unit = unit.trim();
+    UNITS.iter().find(|(u, _)| *u == unit).map(|(_, f)| *f)
+}
+
+impl DotthzFile {
+    /// Write the attributes of a dataset.
+    ///
+    /// Units, labels, sampling interval, offset and scale that are not set are removed from the
+    /// dataset, free-form attributes are added or overwritten. Nothing is written if a free-form
+    /// attribute uses a reserved name.
+    pub fn set_dataset_attributes(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        attributes: &DatasetAttributes,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(key) = attributes
+            .extra
+            .keys()
+            .find(|key| RESERVED.contains(&key.as_str()))
+        {
+            return Err(format!("`{}` cannot be used as free-form attribute", key).into());
+        }
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        write_list_attr(&dataset, UNIT, &attributes.units)?;
+        write_list_attr(&dataset, LABEL, &attributes.labels)?;
+        write_f64_attr(&dataset, SAMPLING_INTERVAL, attributes.sampling_interval)?;
+        write_f64_attr(&dataset, OFFSET, attributes.offset)?;
+        write_f64_attr(&dataset, SCALE, attributes.scale)?;
+        for alias in OFFSET_ALIASES.iter().chain(SCALE_ALIASES.iter()) {
+            if dataset.attr(alias).is_ok() {
+                dataset.delete_attr(alias)?;
+            }
+        }
+        for (key, value) in &attributes.extra {
+            write_string_attr(&dataset, key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Read the attributes of a dataset.
+    ///
+    /// Units and labels written by other tools as `units` and `long_name` are recognised too for
+    /// 1D datasets. They describe a variable as a whole, so they are not used for the rows of
+    /// multi-dimensional datasets. Offset and scale written as `offset` and `scale` by earlier
+    /// versions are read as well.
+    pub fn get_dataset_attributes(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> hdf5::Result<DatasetAttributes> {
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        let use_aliases = dataset.ndim() <= 1;
+        let read_list = |name: &str, aliases: &[&str]| {
+            std::iter::once(name)
+                .chain(aliases.iter().copied().filter(|_| use_aliases))
+                .find_map(|name| read_string_attr(&dataset, name))
+                .map(|value| value.split(", ").map(String::from).collect())
+                .unwrap_or_default()
+        };
+        let read_f64 = |name: &str, aliases: &[&str]| {
+            std::iter::once(name)
+                .chain(aliases.iter().copied())
+                .find_map(|name| dataset.attr(name).and_then(|a| a.read_scalar::<f64>()).ok())
+        };
+
+        let mut extra = IndexMap::new();
+        for name in dataset.attr_names()? {
+            if RESERVED.contains(&name.as_str()) {
+                continue;
+            }
+            if let Some(value) = read_string_attr(&dataset, &name) {
+                extra.insert(name, value);
+            }
+        }
+
+        Ok(DatasetAttributes {
+            units: read_list(UNIT, &UNIT_ALIASES),
+            labels: read_list(LABEL, &LABEL_ALIASES),
+            sampling_interval: read_f64(SAMPLING_INTERVAL, &[]),
+            offset: read_f64(OFFSET, &OFFSET_ALIASES),
+            scale: read_f64(SCALE, &SCALE_ALIASES),
+            extra,
+        })
+    }
+}
+
+fn read_string_attr(dataset: &Dataset, name: &str) -> Option<String> {
+    dataset
+        .attr(name)
+        .and_then(|a| a.read_scalar::<VarLenUnicode>())
+        .ok()
+        .map(|v| v.to_string())
+}
+
+/// Write a string attribute, replacing an existing attribute of any type.
+pub(crate) fn write_string_attr(
+    location: &Location,
+    name: &str,
+    value: &str,
+) -> Result<(), Box<dyn Error>> {
+    if location.attr(name).is_ok() {
+        location.delete_attr(name)?;
+    }
+    location
+        .new_attr::<VarLenUnicode>()
+        .create(name)?
+        .write_scalar(&VarLenUnicode::from_str(value)?)?;
+    Ok(())
+}
+
+/// Write a list joined with `", "` as in `dsDescription`, removing the attribute if empty.
+fn write_list_attr(dataset: &Dataset, name: &str, values: &[String]) -> Result<(), Box<dyn Error>> {
+    if values.is_empty() {
+        if dataset.attr(name).is_ok() {
+            dataset.delete_attr(name)?;
+        }
+        Ok(())
+    } else {
+        write_string_attr(dataset, name, &values.join(", "))
+    }
+}
+
+/// Write a number attribute, removing the attribute if `None`.
+fn write_f64_attr(dataset: &Dataset, name: &str, value: Option<f64>) -> Result<(), Box<dyn Error>> {
+    if dataset.attr(name).is_ok() {
+        dataset.delete_attr(name)?;
+    }
+    if let Some(value) = value {
+        dataset
+            .new_attr::<f64>()
+            .create(name)?
+            .write_scalar(&value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,567 @@
+use crate::DotthzMetaData;
+use hdf5::types::VarLenUnicode;
+use hdf5::File;
+use ndarray::{ArrayD, ArrayViewD};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Value of a group attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    /// A number, like numeric `mdN` entries.
+    Number(f32),
+    /// One string for scalar attributes, or the entries of a string array.
+    Text(Vec<String>),
+}
+
+/// Storage of groups, attributes and n-dimensional datasets underlying a dotThz file.
+///
+/// Groups are addressed by their path, e.g. `/Measurement` or `/campaign/sample`, `/` being the
+/// root group. The dotThz meta-data logic is implemented once on top of this trait, so it works
+/// the same for [`Hdf5Backend`] and for the [`MemoryBackend`] used in tests.
+///
+/// A [`DotthzFile`](crate::DotthzFile) reaches its groups, meta-data and `f64` datasets through
+/// its backend, see [`DotthzFile::with_backend`](crate::DotthzFile::with_backend). Typed and
+/// compressed datasets, dimension scales and SWMR access use the HDF5 file of an
+/// [`Hdf5Backend`] directly.
+pub trait Backend {
+    /// Full paths of the groups directly below `group`.
+    fn group_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>>;
+
+    /// Whether the group exists.
+    fn has_group(&self, group: &str) -> bool;
+
+    /// Create a group, its parent must exist.
+    fn create_group(&mut self, group: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Delete a group with everything below it.
+    fn delete_group(&mut self, group: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Names of the attributes of a group.
+    fn attr_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>>;
+
+    /// Read an attribute of a group, `None` if it does not exist.
+    fn read_attr(&self, group: &str, name: &str) -> Result<Option<AttributeValue>, Box<dyn Error>>;
+
+    /// Write an attribute of a group, creating it if it does not exist.
+    ///
+    /// An existing attribute keeps its type and shape as in HDF5: writing a number to a text
+    /// attribute, or a different number of strings, fails. Delete the attribute to change them.
+    fn write_attr(
+        &mut self,
+        group: &str,
+        name: &str,
+        value: &AttributeValue,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Delete an attribute of a group.
+    fn delete_attr(&mut self, group: &str, name: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Names of the datasets of a group.
+    fn dataset_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>>;
+
+    /// Read a dataset of a group.
+    fn read_dataset(&self, group: &str, name: &str) -> Result<ArrayD<f64>, Box<dyn Error>>;
+
+    /// Create a dataset in a group.
+    fn write_dataset(
+        &mut self,
+        group: &str,
+        name: &str,
+        data: ArrayViewD<'_, f64>,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Read the meta-data of a group, see
+    /// [`DotthzFile::get_meta_data`](crate::DotthzFile::get_meta_data).
+    fn get_meta_data(&self, group: &str) -> Result<DotthzMetaData, Box<dyn Error>> {
+        if !self.has_group(group) {
+            return Err(format!("group `{}` does not exist", group).into());
+        }
+        // attributes that cannot be read are treated as missing
+        let text = |name: &str| match self.read_attr(group, name) {
+            Ok(Some(AttributeValue::Text(values))) => Some(values),
+            _ => None,
+        };
+        let first = |name: &str| text(name).and_then(|values| values.into_iter().next());
+
+        let mut meta_data = DotthzMetaData::default();
+        if let Some(instrument) = first("instrument") {
+            meta_data.instrument = instrument;
+        }
+
+        // Load dataset descriptions
+        if let Some(ds_description) = text("dsDescription") {
+            meta_data.ds_description = ds_description
+                .iter()
+                .flat_map(|s| s.split(", ").map(String::from))
+                .collect();
+        }
+
+        if let Some(md_description) = text("mdDescription") {
+            // a single entry holds all keys separated by ", "
+            let descriptions: Vec<String> = if md_description.len() == 1 {
+                md_description[0].split(", ").map(String::from).collect()
+            } else {
+                md_description
+            };
+
+            for (i, description) in descriptions.iter().enumerate() {
+                let value = match self.read_attr(group, &format!("md{}", i + 1)) {
+                    Ok(Some(AttributeValue::Number(md))) => Some(format!("{}", md)),
+                    Ok(Some(AttributeValue::Text(md))) => md.into_iter().next(),
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    meta_data.md.insert(description.to_string(), value);
+                }
+            }
+        }
+
+        if let Some(mode) = first("mode") {
+            meta_data.mode = mode;
+        }
+        if let Some(version) = first("thzVer") {
+            meta_data.version = version;
+        }
+        if let Some(description) = first("description") {
+            meta_data.description = description;
+        }
+        if let Some(time) = first("time") {
+            meta_data.time = time;
+        }
+        if let Some(date) = first("date") {
+            meta_data.date = date;
+        }
+
+        if let Some(user_info) = first("user") {
+            // Check each part individually to handle cases where fewer than 4 parts are available
+            let user_parts: Vec<&str> = user_info.split('/').collect();
+            if let Some(part) = user_parts.first() {
+                meta_data.orcid = part.trim().into();
+            }
+            if let Some(part) = user_parts.get(1) {
+                meta_data.user = part.trim().into();
+            }
+            if let Some(part) = user_parts.get(2) {
+                meta_data.email = part.trim().into();
+            }
+            if let Some(part) = user_parts.get(3) {
+                meta_data.institution = part.trim().into();
+            }
+        }
+        Ok(meta_data)
+    }
+
+    /// Write the meta-data attributes of a group, keeping its other attributes, including `mdN`
+    /// attributes that are no longer used.
+    fn set_meta_data(
+        &mut self,
+        group: &str,
+        meta_data: &DotthzMetaData,
+    ) -> Result<(), Box<dyn Error>> {
+        let text = |value: &str| AttributeValue::Text(vec![value.to_string()]);
+        self.write_attr(group, "description", &text(&meta_data.description))?;
+        self.write_attr(group, "date", &text(&meta_data.date))?;
+        self.write_attr(group, "instrument", &text(&meta_data.instrument))?;
+        self.write_attr(group, "mode", &text(&meta_data.mode))?;
+        self.write_attr(group, "thzVer", &text(&meta_data.version))?;
+        self.write_attr(group, "time", &text(&meta_data.time))?;
+        let user = format!(
+            "{}/{}/{}/{}",
+            meta_data.orcid, meta_data.user, meta_data.email, meta_data.institution
+        );
+        self.write_attr(group, "user", &text(&user))?;
+
+        // Save additional metadata
+        let md_descriptions = meta_data
+            .md
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(", ");
+        self.write_attr(group, "mdDescription", &text(&md_descriptions))?;
+
+        let attr_names = self.attr_names(group)?;
+        for (i, value) in meta_data.md.values().enumerate() {
+            let attr_name = format!("md{}", i + 1);
+            // existing numeric attributes keep their type, new ones are written as strings
+            let existing = if attr_names.contains(&attr_name) {
+                self.read_attr(group, &attr_name)?
+            } else {
+                None
+            };
+            let value = match (existing, value.parse::<f32>()) {
+                (Some(AttributeValue::Number(_)), Ok(parsed)) => AttributeValue::Number(parsed),
+                (Some(AttributeValue::Number(_)), Err(_)) => {
+                    self.delete_attr(group, &attr_name)?;
+                    text(value)
+                }
+                _ => text(value),
+            };
+            self.write_attr(group, &attr_name, &value)?;
+        }
+
+        // Save dsDescription
+        self.write_attr(
+            group,
+            "dsDescription",
+            &text(&meta_data.ds_description.join(", ")),
+        )
+    }
+
+    /// Replace the meta-data of a group, removing `mdN` attributes that are no longer used, see
+    /// [`DotthzFile::set_meta_data`](crate::DotthzFile::set_meta_data).
+    ///
+    /// Numeric entries stay numeric when they move to another `mdN` attribute or are renamed.
+    fn update_meta_data(
+        &mut self,
+        group: &str,
+        meta_data: &DotthzMetaData,
+    ) -> Result<(), Box<dyn Error>> {
+        let old_meta_data = self.get_meta_data(group)?;
+        let attr_names = self.attr_names(group)?;
+        let old_numeric = (0..old_meta_data.md.len())
+            .map(|i| {
+                matches!(
+                    self.read_attr(group, &format!("md{}", i + 1)),
+                    Ok(Some(AttributeValue::Number(_)))
+                )
+            })
+            .collect::<Vec<bool>>();
+        let numeric = meta_data
+            .md
+            .iter()
+            .enumerate()
+            .map(
+                |(i, (key, value))| match old_meta_data.md.get_index_of(key) {
+                    Some(j) => old_numeric[j],
+                    // a renamed entry keeps its position and value
+                    None => old_numeric.get(i) == Some(&true) && old_meta_data.md[i] == *value,
+                },
+            )
+            .collect::<Vec<bool>>();
+
+        // changed or dropped md entries are recreated
+        for i in 0..old_meta_data.md.len().max(meta_data.md.len()) {
+            let attr_name = format!("md{}", i + 1);
+            let entry = meta_data.md.get_index(i);
+            if entry == old_meta_data.md.get_index(i) {
+                continue;
+            }
+            if attr_names.contains(&attr_name) {
+                self.delete_attr(group, &attr_name)?;
+            }
+            if let Some((_, value)) = entry.filter(|_| numeric.get(i) == Some(&true)) {
+                if let Ok(parsed) = value.parse::<f32>() {
+                    self.write_attr(group, &attr_name, &AttributeValue::Number(parsed))?;
+                }
+            }
+        }
+
+        self.set_meta_data(group, meta_data)
+    }
+}
+
+/// [`Backend`] storing everything in an HDF5 file, used by [`DotthzFile`](crate::DotthzFile).
+#[derive(Debug, Clone)]
+pub struct Hdf5Backend {
+    pub(crate) file: File,
+}
+
+impl From<File> for Hdf5Backend {
+    fn from(file: File) -> Self {
+        Hdf5Backend { file }
+    }
+}
+
+impl Backend for Hdf5Backend {
+    fn group_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self
+            .file
+            .group(group)?
+            .groups()?
+            .iter()
+            .map(|g| g.name())
+            .collect())
+    }
+
+    fn has_group(&self, group: &str) -> bool {
+        self.file.group(group).is_ok()
+    }
+
+    fn create_group(&mut self, group: &str) -> Result<(), Box<dyn Error>> {
+        self.file.create_group(group)?;
+        Ok(())
+    }
+
+    fn delete_group(&mut self, group: &str) -> Result<(), Box<dyn Error>> {
+        self.file.unlink(group)?;
+        Ok(())
+    }
+
+    fn attr_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self.file.group(group)?.attr_names()?)
+    }
+
+    fn read_attr(&self, group: &str, name: &str) -> Result<Option<AttributeValue>, Box<dyn Error>> {
+        let attr = match self.file.group(group)?.attr(name) {
+            Ok(attr) => attr,
+            Err(_) => return Ok(None),
+        };
+        if let Ok(values) = attr.read_raw::<VarLenUnicode>() {
+            return Ok(Some(AttributeValue::Text(
+                values.iter().map(|v| v.to_string()).collect(),
+            )));
+        }
+        Ok(attr
+            .read_raw::<f32>()?
+            .first()
+            .map(|v| AttributeValue::Number(*v)))
+    }
+
+    fn write_attr(
+        &mut self,
+        group: &str,
+        name: &str,
+        value: &AttributeValue,
+    ) -> Result<(), Box<dyn Error>> {
+        let group = self.file.group(group)?;
+        match value {
+            AttributeValue::Number(value) => match group.attr(name) {
+                Ok(attr) if !attr.is_scalar() => attr.write_raw(&[*value])?,
+                Ok(attr) => attr.write_scalar(value)?,
+                Err(_) => group.new_attr::<f32>().create(name)?.write_scalar(value)?,
+            },
+            AttributeValue::Text(values) => {
+                let values = values
+                    .iter()
+                    .map(|v| VarLenUnicode::from_str(v))
+                    .collect::<Result<Vec<VarLenUnicode>, _>>()?;
+                let attr = match group.attr(name) {
+                    Ok(attr) => attr,
+                    Err(_) if values.len() == 1 => {
+                        group.new_attr::<VarLenUnicode>().create(name)?
+                    }
+                    Err(_) => group
+                        .new_attr::<VarLenUnicode>()
+                        .shape(values.len())
+                        .create(name)?,
+                };
+                if attr.is_scalar() && values.len() == 1 {
+                    attr.write_scalar(&values[0])?;
+                } else {
+                    attr.write_raw(values.as_slice())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_attr(&mut self, group: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        self.file.group(group)?.delete_attr(name)?;
+        Ok(())
+    }
+
+    fn dataset_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self
+            .file
+            .group(group)?
+            .datasets()?
+            .iter()
+            .map(|d| {
+                let name = d.name();
+                name.rsplit('/').next().unwrap_or(&name).to_string()
+            })
+            .collect())
+    }
+
+    fn read_dataset(&self, group: &str, name: &str) -> Result<ArrayD<f64>, Box<dyn Error>> {
+        Ok(self.file.group(group)?.dataset(name)?.read_dyn::<f64>()?)
+    }
+
+    fn write_dataset(
+        &mut self,
+        group: &str,
+        name: &str,
+        data: ArrayViewD<'_, f64>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.file
+            .group(group)?
+            .new_dataset::<f64>()
+            .shape(data.shape())
+            .create(name)?
+            .write(data)?;
+        Ok(())
+    }
+}
+
+/// [`Backend`] keeping everything in memory, to test meta-data and dataset logic without
+/// touching the disk or libhdf5.
+///
+/// Groups, attributes and datasets are listed in alphabetical order, as by HDF5.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryBackend {
+    groups: BTreeMap<String, MemoryGroup>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+struct MemoryGroup {
+    attributes: BTreeMap<String, AttributeValue>,
+    datasets: BTreeMap<String, ArrayD<f64>>,
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        MemoryBackend::new()
+    }
+}
+
+impl MemoryBackend {
+    /// An empty backend holding only the root group.
+    pub fn new() -> Self {
+        MemoryBackend {
+            groups: BTreeMap::from([("/".to_string(), MemoryGroup::default())]),
+        }
+    }
+
+    fn group(&self, group: &str) -> Result<&MemoryGroup, Box<dyn Error>> {
+        self.groups
+            .get(&normalize(group))
+            .ok_or_else(|| format!("group `{}` does not exist", group).into())
+    }
+
+    fn group_mut(&mut self, group: &str) -> Result<&mut MemoryGroup, Box<dyn Error>> {
+        self.groups
+            .get_mut(&normalize(group))
+            .ok_or_else(|| format!("group `{}` does not exist", group).into())
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn group_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        self.group(group)?;
+        let parent = normalize(group);
+        Ok(self
+            .groups
+            .keys()
+            .filter(|path| path.as_str() != "/" && parent_of(path) == parent)
+            .cloned()
+            .collect())
+    }
+
+    fn has_group(&self, group: &str) -> bool {
+        self.groups.contains_key(&normalize(group))
+    }
+
+    fn create_group(&mut self, group: &str) -> Result<(), Box<dyn Error>> {
+        let path = normalize(group);
+        if self.groups.contains_key(&path) {
+            return Err(format!("group `{}` already exists", group).into());
+        }
+        self.group(&parent_of(&path))?;
+        self.groups.insert(path, MemoryGroup::default());
+        Ok(())
+    }
+
+    fn delete_group(&mut self, group: &str) -> Result<(), Box<dyn Error>> {
+        let path = normalize(group);
+        self.group(&path)?;
+        let prefix = format!("{}/", path);
+        self.groups
+            .retain(|p, _| p != &path && !p.starts_with(&prefix));
+        Ok(())
+    }
+
+    fn attr_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self.group(group)?.attributes.keys().cloned().collect())
+    }
+
+    fn read_attr(&self, group: &str, name: &str) -> Result<Option<AttributeValue>, Box<dyn Error>> {
+        Ok(self.group(group)?.attributes.get(name).cloned())
+    }
+
+    fn write_attr(
+        &mut self,
+        group: &str,
+        name: &str,
+        value: &AttributeValue,
+    ) -> Result<(), Box<dyn Error>> {
+        let attributes = &mut self.group_mut(group)?.attributes;
+        match (attributes.get(name), value) {
+            (Some(AttributeValue::Number(_)), AttributeValue::Text(_))
+            | (Some(AttributeValue::Text(_)), AttributeValue::Number(_)) => {
+                return Err(format!("attribute `{}` has a different type", name).into());
+            }
+            (Some(AttributeValue::Text(old)), AttributeValue::Text(new))
+                if old.len() != new.len() =>
+            {
+                return Err(format!(
+                    "attribute `{}` holds {} strings, cannot write {}",
+                    name,
+                    old.len(),
+                    new.len()
+                )
+                .into());
+            }
+            _ => {}
+        }
+        attributes.insert(name.to_string(), value.clone());
+        Ok(())
+    }
+
+    fn delete_attr(&mut self, group: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        self.group_mut(group)?
+            .attributes
+            .remove(name)
+            .ok_or_else(|| format!("attribute `{}` does not exist", name))?;
+        Ok(())
+    }
+
+    fn dataset_names(&self, group: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(self.group(group)?.datasets.keys().cloned().collect())
+    }
+
+    fn read_dataset(&self, group: &str, name: &str) -> Result<ArrayD<f64>, Box<dyn Error>> {
+        Ok(self
+            .group(group)?
+            .datasets
+            .get(name)
+            .ok_or_else(|| format!("dataset `{}` does not exist", name))?
+            .clone())
+    }
+
+    fn write_dataset(
+        &mut self,
+        group: &str,
+        name: &str,
+        data: ArrayViewD<'_, f64>,
+    ) -> Result<(), Box<dyn Error>> {
+        let datasets = &mut self.group_mut(group)?.datasets;
+        if datasets.contains_key(name) {
+            return Err(format!("dataset `{}` already exists", name).into());
+        }
+        datasets.insert(name.to_string(), data.to_owned());
+        Ok(())
+    }
+}
+
+/// Normalize a group path to the form `/a/b`, `/` for the root group.
+fn normalize(group: &str) -> String {
+    format!(
+        "/{}",
+        group
+            .split('/')
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<&str>>()
+            .join("/")
+    )
+}
+
+/// The path of the parent of a normalized group path.
+fn parent_of(path: &str) -> String {
+    match path.rsplit_once('/') {
+        Some(("", _)) | None => "/".to_string(),
+        Some((parent, _)) => parent.to_string(),
+    }
+}
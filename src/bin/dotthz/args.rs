@@ -0,0 +1,6 @@
+/// Parse a `KEY=VALUE` argument, splitting at the first `=`.
+pub fn parse_pair(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{}`", s))
+}
@@ -0,0 +1,154 @@
+use crate::args::parse_pair;
+use crate::paths::collect_thz_files;
+use clap::Args;
+use dotthz::{diff_meta_data, DotthzFile, MetaDataEdit};
+use std::error::Error;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Exit code when at least one file could not be edited.
+const EXIT_FAILED: u8 = 1;
+/// Exit code when the inputs could not be resolved.
+const EXIT_USAGE: u8 = 2;
+
+/// Edits are applied in the order renames, sets, unsets.
+///
+/// KEY is a field (`description`, `date`, `time`, `mode`, `instrument`, `version`, `user`,
+/// `email`, `orcid`, `institution`) or an md entry such as `Humidity (%)`. Prefix md entries
+/// with `md:` if their name collides with a field.
+#[derive(Args)]
+pub struct EditArgs {
+    /// Files, directories or glob patterns to edit.
+    #[arg(required = true)]
+    paths: Vec<String>,
+
+    /// Set a field or md entry.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_pair)]
+    set: Vec<(String, String)>,
+
+    /// Clear a field or remove an md entry.
+    #[arg(long = "unset", value_name = "KEY")]
+    unset: Vec<String>,
+
+    /// Rename an md entry.
+    #[arg(long = "rename", value_name = "OLD=NEW", value_parser = parse_pair)]
+    rename: Vec<(String, String)>,
+
+    /// Only edit the named groups (default: all measurement groups, including nested ones).
+    ///
+    /// Edits apply to the meta-data stored on each group, values inherited from parent groups
+    /// are not copied into it.
+    #[arg(short, long = "group", value_name = "GROUP")]
+    groups: Vec<String>,
+
+    /// Descend into sub-directories.
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// Print the changes without writing them.
+    #[arg(short = 'n', long)]
+    dry_run: bool,
+}
+
+impl EditArgs {
+    fn edits(&self) -> Vec<MetaDataEdit> {
+        let renames = self.rename.iter().map(|(from, to)| MetaDataEdit::Rename {
+            from: from.clone(),
+            to: to.clone(),
+        });
+        let sets = self.set.iter().map(|(key, value)| MetaDataEdit::Set {
+            key: key.clone(),
+            value: value.clone(),
+        });
+        let unsets = self
+            .unset
+            .iter()
+            .map(|key| MetaDataEdit::Unset { key: key.clone() });
+        renames.chain(sets).chain(unsets).collect()
+    }
+}
+
+pub fn run(args: EditArgs) -> ExitCode {
+    let edits = args.edits();
+    if edits.is_empty() {
+        eprintln!("error: nothing to do, pass at least one of --set, --unset or --rename");
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    let files = match collect_thz_files(&args.paths, args.recursive) {
+        Ok(files) if files.is_empty() => {
+            eprintln!("error: no .thz files found");
+            return ExitCode::from(EXIT_USAGE);
+        }
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let mut failed = 0;
+    for file in files.iter() {
+        if let Err(e) = edit_file(file, &edits, &args) {
+            eprintln!("{}: error: {}", file.display(), e);
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        ExitCode::from(EXIT_FAILED)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn edit_file(path: &Path, edits: &[MetaDataEdit], args: &EditArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = if args.dry_run {
+        DotthzFile::open(&path.to_path_buf())?
+    } else {
+        DotthzFile::open_rw(path)?
+    };
+
+    let group_names = if args.groups.is_empty() {
+        file.get_measurement_group_names()?
+    } else {
+        args.groups.clone()
+    };
+
+    // apply all edits before writing, so a failing edit leaves the file untouched
+    let mut updates = Vec::new();
+    for group_name in group_names {
+        let old_meta_data = file.get_meta_data(&group_name)?;
+        let mut meta_data = old_meta_data.clone();
+        for edit in edits {
+            edit.apply(&mut meta_data)
+                .map_err(|e| format!("{}: {}: {}", group_name, edit, e))?;
+        }
+        let changes = diff_meta_data(&old_meta_data, &meta_data);
+        if !changes.is_empty() {
+            updates.push((group_name, meta_data, changes));
+        }
+    }
+
+    if updates.is_empty() {
+        println!("{}: unchanged", path.display());
+        return Ok(());
+    }
+
+    for (group_name, meta_data, changes) in updates {
+        println!("{}: {}", path.display(), group_name);
+        for change in changes {
+            for line in change.to_string().lines() {
+                println!("  {}", line);
+            }
+        }
+        if !args.dry_run {
+            file.set_meta_data(&group_name, &meta_data)?;
+        }
+    }
+
+    if !args.dry_run {
+        file.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use clap::Args;
+use dotthz::{export_csv, export_csv_to_path, CsvExportOptions, DotthzFile};
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Exit code when at least one group could not be exported.
+const EXIT_FAILED: u8 = 1;
+
+/// Writes one delimited text file per group, named `<file>_<group>.csv`.
+#[derive(Args)]
+pub struct ExportCsvArgs {
+    /// The .thz file to export.
+    input: PathBuf,
+
+    /// Only export the named groups (default: all measurement groups, including nested ones).
+    #[arg(short, long = "group", value_name = "GROUP")]
+    groups: Vec<String>,
+
+    /// Only export the named datasets (default: all datasets).
+    #[arg(short, long = "dataset", value_name = "DATASET")]
+    datasets: Vec<String>,
+
+    /// Output directory (default: next to the input), `-` writes to stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Column delimiter.
+    #[arg(long, default_value_t = ',')]
+    delimiter: char,
+
+    /// Write the meta-data as commented lines before the header.
+    #[arg(short, long)]
+    meta: bool,
+}
+
+pub fn run(args: ExportCsvArgs) -> ExitCode {
+    match export(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}: error: {}", args.input.display(), e);
+            ExitCode::from(EXIT_FAILED)
+        }
+    }
+}
+
+fn export(args: &ExportCsvArgs) -> Result<(), Box<dyn Error>> {
+    let file = DotthzFile::open(&args.input)?;
+    let options = CsvExportOptions {
+        delimiter: args.delimiter,
+        datasets: args.datasets.clone(),
+        meta_data_preamble: args.meta,
+    };
+    let group_names = if args.groups.is_empty() {
+        file.get_measurement_group_names()?
+    } else {
+        args.groups.clone()
+    };
+
+    for group_name in group_names.iter() {
+        match args.output.as_deref() {
+            Some(path) if path == Path::new("-") => {
+                export_csv(&file, group_name, io::stdout().lock(), &options)?
+            }
+            output => {
+                let path = output_path(&args.input, output, group_name);
+                export_csv_to_path(&file, group_name, &path, &options)?;
+                println!(
+                    "{}:{} -> {}",
+                    args.input.display(),
+                    group_name,
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn output_path(input: &Path, output: Option<&Path>, group_name: &str) -> PathBuf {
+    let stem = input
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let group = group_name.trim_start_matches('/').replace('/', "_");
+    let dir = output
+        .or_else(|| input.parent())
+        .unwrap_or_else(|| Path::new("."));
+    dir.join(format!("{}_{}.csv", stem, group))
+}
@@ -0,0 +1,168 @@
+use crate::args::parse_pair;
+use clap::Args;
+use dotthz::{
+    import_csv, load_meta_data, CsvImportOptions, CsvOptions, DotthzFile, DotthzMetaData,
+    MetaDataEdit,
+};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Exit code when at least one file could not be imported.
+const EXIT_FAILED: u8 = 1;
+/// Exit code for invalid arguments.
+const EXIT_USAGE: u8 = 2;
+
+/// Each input becomes one group, named after the file unless `--group` is given. The output file
+/// is created if it does not exist, otherwise the groups are added to it.
+#[derive(Args)]
+pub struct ImportCsvArgs {
+    /// Delimited text files to import.
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// The .thz file to write.
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Group name, only valid with a single input.
+    #[arg(short, long)]
+    group: Option<String>,
+
+    /// JSON or TOML sidecar holding the meta-data.
+    #[arg(short, long, value_name = "FILE")]
+    meta: Option<PathBuf>,
+
+    /// Set a meta-data field or md entry, applied after the sidecar.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_pair)]
+    set: Vec<(String, String)>,
+
+    /// Column delimiter, `tab` or `space` for whitespace (default: inferred).
+    #[arg(long, value_parser = parse_delimiter)]
+    delimiter: Option<char>,
+
+    /// Decimal separator (default: inferred).
+    #[arg(long)]
+    decimal: Option<char>,
+
+    /// The first line holds column names (default: inferred).
+    #[arg(long, conflicts_with = "no_header")]
+    header: bool,
+
+    /// The first line holds data (default: inferred).
+    #[arg(long)]
+    no_header: bool,
+
+    /// Index of the time column, starting at 0.
+    #[arg(long, default_value_t = 0)]
+    time_column: usize,
+
+    /// Indices of the signal columns (default: all but the time column).
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<usize>,
+}
+
+pub fn run(args: ImportCsvArgs) -> ExitCode {
+    if args.group.is_some() && args.inputs.len() > 1 {
+        eprintln!("error: --group can only be used with a single input");
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    let meta_data = match meta_data(&args) {
+        Ok(meta_data) => meta_data,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let options = CsvImportOptions {
+        csv: CsvOptions {
+            delimiter: args.delimiter,
+            decimal_separator: args.decimal,
+            has_header: match (args.header, args.no_header) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            comment: None,
+        },
+        time_column: Some(args.time_column),
+        signal_columns: args.columns.clone(),
+    };
+
+    let mut file = match DotthzFile::append(&args.output) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{}: error: {}", args.output.display(), e);
+            return ExitCode::from(EXIT_FAILED);
+        }
+    };
+
+    let mut failed = 0;
+    for input in args.inputs.iter() {
+        let group_name = args.group.clone().unwrap_or_else(|| group_name(input));
+        match import_csv(&mut file, &group_name, input, &meta_data, &options) {
+            Ok(()) => println!(
+                "{} -> {}:{}",
+                input.display(),
+                args.output.display(),
+                group_name
+            ),
+            Err(e) => {
+                eprintln!("{}: error: {}", input.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    if let Err(e) = file.flush() {
+        eprintln!("{}: error: {}", args.output.display(), e);
+        failed += 1;
+    }
+
+    if failed > 0 {
+        ExitCode::from(EXIT_FAILED)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn meta_data(args: &ImportCsvArgs) -> Result<DotthzMetaData, Box<dyn Error>> {
+    let mut meta_data = match &args.meta {
+        Some(path) => load_meta_data(path)?,
+        None => DotthzMetaData::default(),
+    };
+    for (key, value) in args.set.iter() {
+        MetaDataEdit::Set {
+            key: key.clone(),
+            value: value.clone(),
+        }
+        .apply(&mut meta_data)?;
+    }
+    if meta_data.version.is_empty() {
+        meta_data.version = "1.00".to_string();
+    }
+    Ok(meta_data)
+}
+
+fn group_name(input: &Path) -> String {
+    input
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Measurement".to_string())
+}
+
+fn parse_delimiter(s: &str) -> Result<char, String> {
+    match s {
+        "tab" | "\\t" => Ok('\t'),
+        "space" => Ok(' '),
+        _ => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!("expected a single character, got `{}`", s)),
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Command line tool to inspect and manipulate dotThz files.
+
+mod args;
+mod edit;
+mod export_csv;
+mod import_csv;
+mod paths;
+mod validate;
+
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+/// Inspect and manipulate dotThz files.
+#[derive(Parser)]
+#[command(name = "dotthz", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Change meta-data of groups in .thz files.
+    Edit(edit::EditArgs),
+    /// Export groups of a .thz file as delimited text.
+    ExportCsv(export_csv::ExportCsvArgs),
+    /// Import delimited text time-domain traces into a .thz file.
+    ImportCsv(import_csv::ImportCsvArgs),
+    /// Validate .thz files against the dotThz standard.
+    Validate(validate::ValidateArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Edit(args) => edit::run(args),
+        Command::ExportCsv(args) => export_csv::run(args),
+        Command::ImportCsv(args) => import_csv::run(args),
+        Command::Validate(args) => validate::run(args),
+    }
+}
@@ -0,0 +1,31 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Expand the command line inputs into a sorted, de-duplicated list of .thz files.
+///
+/// Inputs may be files, directories or glob patterns, which is useful on shells that do not
+/// expand them, see [`dotthz::collect_thz_files`].
+pub fn collect_thz_files(
+    inputs: &[String],
+    recursive: bool,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        if is_glob(input) {
+            let matched = paths.len();
+            for entry in glob::glob(input)? {
+                paths.push(entry?);
+            }
+            if paths.len() == matched {
+                return Err(format!("pattern `{}` did not match any files", input).into());
+            }
+        } else {
+            paths.push(PathBuf::from(input));
+        }
+    }
+    dotthz::collect_thz_files(&paths, recursive)
+}
+
+fn is_glob(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
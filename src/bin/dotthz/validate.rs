@@ -0,0 +1,79 @@
+use crate::paths::collect_thz_files;
+use clap::Args;
+use dotthz::{validate_file, Severity};
+use std::process::ExitCode;
+
+/// Exit code when at least one file failed validation.
+const EXIT_INVALID: u8 = 1;
+/// Exit code when the inputs could not be resolved.
+const EXIT_USAGE: u8 = 2;
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// Files, directories or glob patterns to validate.
+    #[arg(required = true)]
+    paths: Vec<String>,
+
+    /// Descend into sub-directories.
+    #[arg(short, long)]
+    recursive: bool,
+
+    /// Treat warnings as errors.
+    #[arg(long)]
+    strict: bool,
+
+    /// Only print files with findings.
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+pub fn run(args: ValidateArgs) -> ExitCode {
+    let files = match collect_thz_files(&args.paths, args.recursive) {
+        Ok(files) if files.is_empty() => {
+            eprintln!("error: no .thz files found");
+            return ExitCode::from(EXIT_USAGE);
+        }
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let mut failed = 0;
+    for file in files.iter() {
+        let report = validate_file(file);
+        let invalid = report.has_errors() || (args.strict && report.has_warnings());
+        if invalid {
+            failed += 1;
+        }
+
+        if report.is_clean() {
+            if !args.quiet {
+                println!("{}: ok", report.path.display());
+            }
+            continue;
+        }
+
+        let status = if invalid { "FAILED" } else { "ok" };
+        println!("{}: {}", report.path.display(), status);
+        for diagnostic in report.diagnostics.iter() {
+            let diagnostic = if args.strict && diagnostic.severity == Severity::Warning {
+                format!("{} (strict)", diagnostic)
+            } else {
+                diagnostic.to_string()
+            };
+            println!("  {}", diagnostic);
+        }
+    }
+
+    if !args.quiet || failed > 0 {
+        println!("{} of {} files failed validation", failed, files.len());
+    }
+
+    if failed > 0 {
+        ExitCode::from(EXIT_INVALID)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
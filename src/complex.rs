@@ -0,0 +1,139 @@
+use crate::DotthzFile;
+use hdf5::H5Type;
+use ndarray::{ArrayD, ArrayView, Dimension, IxDyn};
+use num_complex::Complex;
+use std::error::Error;
+use std::fmt::Debug;
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// Floating point types usable as real and imaginary part of complex datasets, `f32` and `f64`.
+pub trait ComplexFloat: H5Type + Copy + Debug + sealed::Sealed {}
+
+impl ComplexFloat for f32 {}
+impl ComplexFloat for f64 {}
+
+/// How complex values are stored in a dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComplexLayout {
+    /// HDF5 compound type with the fields `r` and `i`, compatible with h5py.
+    ///
+    /// Requires the `hdf5-complex` feature.
+    Native,
+    /// Real dataset with a trailing dimension of 2 holding real and imaginary part.
+    Interleaved,
+}
+
+impl Default for ComplexLayout {
+    /// `Native` with the `hdf5-complex` feature, `Interleaved` otherwise.
+    fn default() -> Self {
+        if cfg!(feature = "hdf5-complex") {
+            ComplexLayout::Native
+        } else {
+            ComplexLayout::Interleaved
+        }
+    }
+}
+
+impl DotthzFile {
+    /// Add a complex dataset to a group, using the default [`ComplexLayout`].
+    pub fn add_complex_dataset<T, D>(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dataset: ArrayView<'_, Complex<T>, D>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: ComplexFloat,
+        D: Dimension,
+    {
+        self.add_complex_dataset_with_layout(
+            group_name,
+            dataset_name,
+            dataset,
+            ComplexLayout::default(),
+        )
+    }
+
+    /// Add a complex dataset to a group, stored in the given layout.
+    pub fn add_complex_dataset_with_layout<T, D>(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dataset: ArrayView<'_, Complex<T>, D>,
+        layout: ComplexLayout,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: ComplexFloat,
+        D: Dimension,
+    {
+        match layout {
+            ComplexLayout::Native => {
+                #[cfg(feature = "hdf5-complex")]
+                return self.add_dataset(group_name, dataset_name, dataset);
+                #[cfg(not(feature = "hdf5-complex"))]
+                return Err("the native complex layout requires the `hdf5-complex` feature".into());
+            }
+            ComplexLayout::Interleaved => {
+                let mut shape = dataset.shape().to_vec();
+                shape.push(2);
+                let values = dataset
+                    .iter()
+                    .flat_map(|c| [c.re, c.im])
+                    .collect::<Vec<T>>();
+                let data = ArrayD::from_shape_vec(IxDyn(&shape), values)?;
+                self.add_dataset(group_name, dataset_name, data.view())
+            }
+        }
+    }
+
+    /// Read a complex dataset stored in either [`ComplexLayout`].
+    ///
+    /// Native complex datasets can only be read with the `hdf5-complex` feature. Real datasets
+    /// are read as interleaved layout and must have a trailing dimension of 2.
+    pub fn read_complex<T: ComplexFloat>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> Result<ArrayD<Complex<T>>, Box<dyn Error>> {
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        #[cfg(feature = "hdf5-complex")]
+        if dataset.dtype()?.is::<Complex<T>>() {
+            return Ok(dataset.read_dyn::<Complex<T>>()?);
+        }
+        if matches!(
+            dataset.dtype()?.to_descriptor()?,
+            hdf5::types::TypeDescriptor::Compound(_)
+        ) {
+            return Err(format!(
+                "dataset `{}` has a compound type, native complex datasets need the \
+                 `hdf5-complex` feature and the precision they are stored with",
+                dataset_name
+            )
+            .into());
+        }
+
+        let data = dataset.read_dyn::<T>()?;
+        let shape = data.shape();
+        if shape.last() != Some(&2) {
+            return Err(format!(
+                "dataset `{}` of shape {:?} has no trailing dimension of 2",
+                dataset_name, shape
+            )
+            .into());
+        }
+        let shape = shape[..shape.len() - 1].to_vec();
+        let values = data
+            .iter()
+            .copied()
+            .collect::<Vec<T>>()
+            .chunks(2)
+            .map(|pair| Complex::new(pair[0], pair[1]))
+            .collect();
+        Ok(ArrayD::from_shape_vec(IxDyn(&shape), values)?)
+    }
+}
@@ -0,0 +1,510 @@
+use crate::dotthz::ds_index;
+use crate::{DotthzFile, DotthzMetaData};
+use ndarray::Array2;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Delimiters tried when none is given, in order of preference.
+const DELIMITERS: [char; 3] = ['\t', ';', ','];
+
+/// Options controlling how delimited text is parsed.
+///
+/// Every option left as `None` is inferred from the data.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// Column delimiter. `' '` splits on any run of whitespace.
+    pub delimiter: Option<char>,
+    /// Decimal separator, `'.'` or `','`.
+    pub decimal_separator: Option<char>,
+    /// Whether the first non-comment line holds column names.
+    pub has_header: Option<bool>,
+    /// Lines starting with this character are skipped, defaults to `'#'`.
+    pub comment: Option<char>,
+}
+
+/// A table of numeric columns read from delimited text.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CsvTable {
+    /// Column names, taken from the header or generated as `Column N`.
+    pub headers: Vec<String>,
+    /// The values of each column.
+    pub columns: Vec<Vec<f64>>,
+}
+
+impl CsvTable {
+    /// Number of data rows.
+    pub fn len(&self) -> usize {
+        self.columns.first().map_or(0, |c| c.len())
+    }
+
+    /// Returns true if the table holds no data rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index of the column with the given name.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+}
+
+/// Parse delimited text into a [`CsvTable`].
+///
+/// Fields can be quoted as described in RFC 4180: a field enclosed in double quotes may contain
+/// the delimiter, line breaks and double quotes, the latter written twice (`""`).
+pub fn parse_csv(text: &str, options: &CsvOptions) -> Result<CsvTable, Box<dyn Error>> {
+    let comment = options.comment.unwrap_or('#');
+    let lines = records(text, comment);
+    if lines.is_empty() {
+        return Err("no data found".into());
+    }
+
+    let delimiter = options.delimiter.unwrap_or_else(|| infer_delimiter(&lines));
+    let rows = lines
+        .iter()
+        .map(|l| split_line(l, delimiter))
+        .collect::<Vec<Vec<String>>>();
+
+    let decimal_separator = options
+        .decimal_separator
+        .unwrap_or_else(|| infer_decimal_separator(&rows, delimiter));
+    let has_header = options.has_header.unwrap_or_else(|| {
+        rows[0]
+            .iter()
+            .any(|f| parse_number(f, decimal_separator).is_none())
+    });
+
+    let width = rows[0].len();
+    let (headers, data) = if has_header {
+        (rows[0].clone(), &rows[1..])
+    } else {
+        let headers = (1..=width).map(|i| format!("Column {}", i)).collect();
+        (headers, &rows[..])
+    };
+
+    // row numbers in error messages count the header, but not comments
+    let offset = usize::from(has_header) + 1;
+    let mut columns = vec![Vec::with_capacity(data.len()); width];
+    for (i, row) in data.iter().enumerate() {
+        if row.len() != width {
+            return Err(format!(
+                "row {} has {} fields, expected {}",
+                i + offset,
+                row.len(),
+                width
+            )
+            .into());
+        }
+        for (column, field) in columns.iter_mut().zip(row.iter()) {
+            let value = parse_number(field, decimal_separator)
+                .ok_or_else(|| format!("row {}: `{}` is not a number", i + offset, field))?;
+            column.push(value);
+        }
+    }
+    Ok(CsvTable { headers, columns })
+}
+
+/// Read and parse a delimited text file into a [`CsvTable`].
+pub fn read_csv<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<CsvTable, Box<dyn Error>> {
+    parse_csv(&fs::read_to_string(path)?, options)
+}
+
+/// Describes how the columns of a [`CsvTable`] map onto datasets.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CsvImportOptions {
+    /// Parsing options.
+    pub csv: CsvOptions,
+    /// Index of the time column, defaults to the first column.
+    pub time_column: Option<usize>,
+    /// Indices of the signal columns, defaults to all columns but the time column.
+    pub signal_columns: Vec<usize>,
+}
+
+/// Write the columns of `table` as a new dotThz group.
+///
+/// Every signal column becomes a dataset `dsN` of shape `(2, rows)`, holding the time axis in the
+/// first and the signal in the second row. If `meta_data.ds_description` is empty, it is filled
+/// with the column names.
+///
+/// The table is checked completely before the group is created, and the group is removed again
+/// if writing a dataset fails, so a failed import leaves the file unchanged.
+pub fn import_csv_table(
+    file: &mut DotthzFile,
+    group_name: &str,
+    table: &CsvTable,
+    meta_data: &DotthzMetaData,
+    options: &CsvImportOptions,
+) -> Result<(), Box<dyn Error>> {
+    let width = table.columns.len();
+    let time_column = options.time_column.unwrap_or(0);
+    if time_column >= width {
+        return Err(format!(
+            "time column {} out of range ({} columns)",
+            time_column, width
+        )
+        .into());
+    }
+    let signal_columns = if options.signal_columns.is_empty() {
+        (0..width).filter(|&i| i != time_column).collect()
+    } else {
+        options.signal_columns.clone()
+    };
+    if signal_columns.is_empty() {
+        return Err("no signal columns to import".into());
+    }
+    if let Some(&i) = signal_columns.iter().find(|&&i| i >= width) {
+        return Err(format!("signal column {} out of range ({} columns)", i, width).into());
+    }
+
+    if table.is_empty() {
+        return Err("no data rows to import".into());
+    }
+
+    let mut meta_data = meta_data.clone();
+    if meta_data.ds_description.is_empty() {
+        meta_data.ds_description = signal_columns
+            .iter()
+            .map(|&i| table.headers[i].clone())
+            .collect();
+    } else if meta_data.ds_description.len() != signal_columns.len() {
+        return Err(format!(
+            "{} dataset descriptions given for {} signal columns",
+            meta_data.ds_description.len(),
+            signal_columns.len()
+        )
+        .into());
+    }
+    if let Some(description) = meta_data.ds_description.iter().find(|d| d.contains(", ")) {
+        return Err(format!(
+            "dataset description `{}` contains \", \", which separates `dsDescription` entries",
+            description
+        )
+        .into());
+    }
+    if file.get_group(group_name).is_ok() {
+        return Err(format!("group `{}` already exists", group_name).into());
+    }
+
+    // build all datasets before touching the file
+    let time = &table.columns[time_column];
+    let datasets = signal_columns
+        .iter()
+        .map(|&i| {
+            let mut data = Array2::<f64>::zeros((2, table.len()));
+            for (j, (t, s)) in time.iter().zip(table.columns[i].iter()).enumerate() {
+                data[[0, j]] = *t;
+                data[[1, j]] = *s;
+            }
+            data
+        })
+        .collect::<Vec<Array2<f64>>>();
+
+    file.add_group(group_name, &meta_data)?;
+    for (n, data) in datasets.iter().enumerate() {
+        if let Err(e) = file.add_dataset(group_name, &format!("ds{}", n + 1), data.view()) {
+            // do not leave a half-written group behind
+            file.delete_group(group_name)?;
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Read a delimited text file and write it as a new dotThz group, see [`import_csv_table`].
+pub fn import_csv<P: AsRef<Path>>(
+    file: &mut DotthzFile,
+    group_name: &str,
+    path: P,
+    meta_data: &DotthzMetaData,
+    options: &CsvImportOptions,
+) -> Result<(), Box<dyn Error>> {
+    let table = read_csv(path, &options.csv)?;
+    import_csv_table(file, group_name, &table, meta_data, options)
+}
+
+/// Options controlling how a group is exported as delimited text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvExportOptions {
+    /// Column delimiter, defaults to `','`.
+    pub delimiter: char,
+    /// Names of the datasets to export, all datasets of the group if empty.
+    pub datasets: Vec<String>,
+    /// Write the group meta-data, including the meta-data inherited from its parents, as
+    /// `# key: value` comment lines before the header.
+    pub meta_data_preamble: bool,
+}
+
+impl Default for CsvExportOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            datasets: Vec::new(),
+            meta_data_preamble: false,
+        }
+    }
+}
+
+/// Write the datasets of a group as delimited text.
+///
+/// 1D datasets become a single column. 2D datasets are transposed, so a dataset of shape
+/// `(2, n)` becomes the two columns `<description> time` and `<description> signal` with `n`
+/// rows. Column names are taken from `dsDescription`, falling back to the dataset name. Shorter
+/// columns are padded with empty fields.
+pub fn export_csv<W: Write>(
+    file: &DotthzFile,
+    group_name: &str,
+    writer: W,
+    options: &CsvExportOptions,
+) -> Result<(), Box<dyn Error>> {
+    let meta_data = file.get_inherited_meta_data(group_name)?;
+    let mut dataset_names = if options.datasets.is_empty() {
+        file.get_dataset_names(group_name)?
+            .iter()
+            .map(|name| name.rsplit('/').next().unwrap_or(name).to_string())
+            .collect()
+    } else {
+        options.datasets.clone()
+    };
+    if options.datasets.is_empty() {
+        // HDF5 lists datasets alphabetically, put `ds10` after `ds9`
+        dataset_names.sort_by_key(|name| (ds_index(name).unwrap_or(usize::MAX), name.clone()));
+    }
+
+    let mut headers = Vec::new();
+    let mut columns: Vec<Vec<f64>> = Vec::new();
+    for dataset_name in dataset_names.iter() {
+        let description = meta_data
+            .dataset_description(dataset_name)
+            .unwrap_or(dataset_name);
+        let data = file
+            .get_dataset(group_name, dataset_name)?
+            .read_dyn::<f64>()?;
+        let attributes = file.get_dataset_attributes(group_name, dataset_name)?;
+        // append the unit of a row, if known, e.g. `Reference time (ps)`
+        let with_unit = |header: String, row: usize| match attributes.unit(row) {
+            Some(unit) => format!("{} ({})", header, unit),
+            None => header,
+        };
+        match data.ndim() {
+            0 | 1 => {
+                headers.push(with_unit(description.to_string(), 0));
+                columns.push(data.iter().copied().collect());
+            }
+            2 => {
+                let rows = data.shape()[0];
+                for (i, row) in data.outer_iter().enumerate() {
+                    let header = match (rows, i, attributes.label(i)) {
+                        (1, _, _) => description.to_string(),
+                        (_, _, Some(label)) => format!("{} {}", description, label),
+                        (2, 0, None) => format!("{} time", description),
+                        (2, _, None) => format!("{} signal", description),
+                        _ => format!("{} ({})", description, i + 1),
+                    };
+                    headers.push(with_unit(header, i));
+                    columns.push(row.iter().copied().collect());
+                }
+            }
+            n => {
+                return Err(format!(
+                    "dataset `{}` has {} dimensions, only 1D and 2D datasets can be exported",
+                    dataset_name, n
+                )
+                .into())
+            }
+        }
+    }
+
+    let mut writer = BufWriter::new(writer);
+    if options.meta_data_preamble {
+        write_preamble(&mut writer, &meta_data)?;
+    }
+
+    let delimiter = options.delimiter.to_string();
+    let quote = |field: &str| {
+        if field.contains(options.delimiter) || field.contains(['"', '\n']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    };
+    writeln!(
+        writer,
+        "{}",
+        headers
+            .iter()
+            .map(|h| quote(h))
+            .collect::<Vec<String>>()
+            .join(&delimiter)
+    )?;
+
+    let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+    for i in 0..rows {
+        let row = columns
+            .iter()
+            .map(|c| c.get(i).map(|v| v.to_string()).unwrap_or_default())
+            .collect::<Vec<String>>();
+        writeln!(writer, "{}", row.join(&delimiter))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write the datasets of a group to a delimited text file, see [`export_csv`].
+///
+/// The file is only created once the group has been exported, so a failed export does not
+/// leave an empty or partial file behind.
+pub fn export_csv_to_path<P: AsRef<Path>>(
+    file: &DotthzFile,
+    group_name: &str,
+    path: P,
+    options: &CsvExportOptions,
+) -> Result<(), Box<dyn Error>> {
+    let mut buffer = Vec::new();
+    export_csv(file, group_name, &mut buffer, options)?;
+    fs::write(path, buffer)?;
+    Ok(())
+}
+
+fn write_preamble<W: Write>(writer: &mut W, meta_data: &DotthzMetaData) -> io::Result<()> {
+    let fields = [
+        ("description", &meta_data.description),
+        ("user", &meta_data.user),
+        ("email", &meta_data.email),
+        ("orcid", &meta_data.orcid),
+        ("institution", &meta_data.institution),
+        ("instrument", &meta_data.instrument),
+        ("mode", &meta_data.mode),
+        ("date", &meta_data.date),
+        ("time", &meta_data.time),
+        ("thzVer", &meta_data.version),
+    ];
+    for (key, value) in fields {
+        if !value.is_empty() {
+            writeln!(writer, "# {}: {}", key, value)?;
+        }
+    }
+    for (key, value) in meta_data.md.iter() {
+        writeln!(writer, "# {}: {}", key, value)?;
+    }
+    Ok(())
+}
+
+/// Collect the records of `text`, skipping empty lines and comments. A record spans several lines
+/// if a quoted field contains a line break.
+fn records(text: &str, comment: char) -> Vec<String> {
+    let mut records = Vec::new();
+    let mut record = String::new();
+    for line in text.lines() {
+        if record.is_empty() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(comment) {
+                continue;
+            }
+            record.push_str(line);
+        } else {
+            record.push('\n');
+            record.push_str(line);
+        }
+        // an odd number of quotes leaves a quoted field open
+        if record.matches('"').count().is_multiple_of(2) {
+            records.push(std::mem::take(&mut record));
+        }
+    }
+    if !record.is_empty() {
+        records.push(record);
+    }
+    records
+}
+
+/// Split a record into its fields, removing the quotes of quoted fields.
+///
+/// Unquoted fields are trimmed, `' '` as delimiter splits on any run of whitespace.
+fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    let is_delimiter = |c: char| {
+        if delimiter == ' ' {
+            c.is_whitespace()
+        } else {
+            c == delimiter
+        }
+    };
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut chars = line.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c != '"' {
+                field.push(c);
+            } else if chars.next_if_eq(&'"').is_some() {
+                field.push('"');
+            } else {
+                in_quotes = false;
+            }
+        } else if c == '"' && !quoted && field.trim().is_empty() {
+            field.clear();
+            quoted = true;
+            in_quotes = true;
+        } else if is_delimiter(c) {
+            fields.push(finish_field(&mut field, quoted));
+            quoted = false;
+            if delimiter == ' ' {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            }
+        } else if !quoted {
+            field.push(c);
+        }
+    }
+    fields.push(finish_field(&mut field, quoted));
+    fields
+}
+
+/// Take the collected field, trimming it unless it was quoted.
+fn finish_field(field: &mut String, quoted: bool) -> String {
+    let field = std::mem::take(field);
+    if quoted {
+        field
+    } else {
+        field.trim().to_string()
+    }
+}
+
+/// Pick the first delimiter that splits every sampled line into the same number (> 1) of fields.
+fn infer_delimiter(lines: &[String]) -> char {
+    let sample = &lines[..lines.len().min(20)];
+    DELIMITERS
+        .into_iter()
+        .find(|&d| {
+            let width = split_line(&sample[0], d).len();
+            width > 1 && sample.iter().all(|l| split_line(l, d).len() == width)
+        })
+        .unwrap_or(' ')
+}
+
+/// Use `,` as decimal separator if numbers contain commas that do not delimit fields.
+fn infer_decimal_separator(rows: &[Vec<String>], delimiter: char) -> char {
+    if delimiter == ',' {
+        return '.';
+    }
+    let comma_decimal = rows
+        .iter()
+        .take(20)
+        .flatten()
+        .any(|f| f.contains(',') && !f.contains('.') && parse_number(f, ',').is_some());
+    if comma_decimal {
+        ','
+    } else {
+        '.'
+    }
+}
+
+fn parse_number(field: &str, decimal_separator: char) -> Option<f64> {
+    let field = field.trim();
+    if decimal_separator == '.' {
+        field.parse().ok()
+    } else {
+        field.replace(decimal_separator, ".").parse().ok()
+    }
+}
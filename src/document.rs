@@ -0,0 +1,152 @@
+use crate::scales::mark_dimension_scale;
+use crate::{is_dimension_scale, DatasetAttributes, DotthzFile, DotthzMetaData, TypedArray};
+use hdf5::Group;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Serializable snapshot of a whole `DotthzFile`, with all groups, meta-data and datasets.
+///
+/// Any serde format can be used, e.g. `serde_json` for web APIs or text-diffable fixtures.
+/// Note that JSON cannot represent `NaN` or infinite values.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DotthzDocument {
+    /// The groups of the file.
+    pub groups: Vec<GroupDocument>,
+}
+
+/// A group with its meta-data and datasets.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupDocument {
+    /// The name of the group.
+    pub name: String,
+
+    /// The meta-data of the group.
+    #[serde(default)]
+    pub meta_data: DotthzMetaData,
+
+    /// The datasets of the group.
+    #[serde(default)]
+    pub datasets: Vec<DatasetDocument>,
+}
+
+/// A dataset with its shape, data, attributes and axes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetDocument {
+    /// The name of the dataset.
+    pub name: String,
+
+    /// The element type, shape and data of the dataset.
+    #[serde(flatten)]
+    pub data: TypedArray,
+
+    /// The units, labels, scaling and extra attributes of the dataset.
+    #[serde(default, skip_serializing_if = "DatasetAttributes::is_empty")]
+    pub attributes: DatasetAttributes,
+
+    /// Whether the dataset is a dimension scale, see [`DotthzFile::add_axis`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_axis: bool,
+
+    /// The axis attached to each dimension, relative to the group or as absolute path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<Option<String>>,
+}
+
+impl DotthzDocument {
+    /// Read all groups, meta-data and datasets of a file.
+    ///
+    /// Nested groups follow their parent with their full path, e.g. `campaign/sample/300K`.
+    /// Datasets keep their element type for `f32`, `f64`, `i32`, `i64` and native complex, other
+    /// types are converted to `f64`. Dataset attributes and attached axes are kept.
+    pub fn from_file(file: &DotthzFile) -> hdf5::Result<Self> {
+        let mut groups = Vec::new();
+        let root = file.get_group("/")?;
+        collect_groups(file, &root, &mut groups)?;
+        Ok(DotthzDocument { groups })
+    }
+
+    /// Write all groups of the document into a file.
+    ///
+    /// Axes are attached once all datasets of their group are written.
+    pub fn write_into(&self, file: &mut DotthzFile) -> Result<(), Box<dyn Error>> {
+        for group in &self.groups {
+            file.upsert_group(&group.name, &group.meta_data)?;
+            for dataset in &group.datasets {
+                dataset.data.write_into(file, &group.name, &dataset.name)?;
+                if dataset.is_axis {
+                    let written = file.get_dataset(&group.name, &dataset.name)?;
+                    mark_dimension_scale(&written, &dataset.name)?;
+                }
+                if !dataset.attributes.is_empty() {
+                    file.set_dataset_attributes(&group.name, &dataset.name, &dataset.attributes)?;
+                }
+            }
+            for dataset in &group.datasets {
+                for (dim, axis) in dataset.axes.iter().enumerate() {
+                    if let Some(axis) = axis {
+                        file.attach_axis(&group.name, &dataset.name, dim, axis)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a new file holding the document.
+    pub fn create_file(&self, path: &PathBuf) -> Result<DotthzFile, Box<dyn Error>> {
+        let mut file = DotthzFile::create(path)?;
+        self.write_into(&mut file)?;
+        Ok(file)
+    }
+}
+
+fn collect_groups(
+    file: &DotthzFile,
+    parent: &Group,
+    groups: &mut Vec<GroupDocument>,
+) -> hdf5::Result<()> {
+    for group in parent.groups()? {
+        let group_name = group.name();
+        let datasets = group
+            .datasets()?
+            .iter()
+            .map(|dataset| {
+                let path = dataset.name();
+                let name = path.rsplit('/').next().unwrap_or(&path).to_string();
+                let axes = (0..dataset.ndim())
+                    .map(|dim| {
+                        let axis = file
+                            .get_axis(&group_name, &name, dim)
+                            .map_err(|e| hdf5::Error::from(e.to_string()))?;
+                        Ok(axis.map(|axis| {
+                            let axis_path = axis.name();
+                            match axis_path.strip_prefix(&format!("{}/", group_name)) {
+                                Some(relative) if !relative.contains('/') => relative.to_string(),
+                                _ => axis_path,
+                            }
+                        }))
+                    })
+                    .collect::<hdf5::Result<Vec<Option<String>>>>()?;
+                Ok(DatasetDocument {
+                    data: TypedArray::read(dataset)?,
+                    attributes: file.get_dataset_attributes(&group_name, &name)?,
+                    is_axis: is_dimension_scale(dataset),
+                    axes: if axes.iter().all(Option::is_none) {
+                        Vec::new()
+                    } else {
+                        axes
+                    },
+                    name,
+                })
+            })
+            .collect::<hdf5::Result<Vec<DatasetDocument>>>()?;
+        groups.push(GroupDocument {
+            name: group_name.trim_start_matches('/').to_string(),
+            meta_data: file.get_meta_data(&group_name)?,
+            datasets,
+        });
+        collect_groups(file, &group, groups)?;
+    }
+    Ok(())
+}
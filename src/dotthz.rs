@@ -1,18 +1,21 @@
+use crate::backend::{Backend, Hdf5Backend};
+use crate::scales::{attach_scale, detach_scales, mark_dimension_scale};
+use crate::{is_dimension_scale, DatasetOptions, DotthzGroup};
 use hdf5::file::{FileAccess, FileCreate};
-use hdf5::types::VarLenUnicode;
-use hdf5::{Dataset, File, Group, H5Type, OpenMode};
+use hdf5::types::{TypeDescriptor, VarLenUnicode};
+use hdf5::{Dataset, Datatype, File, Group, H5Type, OpenMode};
 use indexmap::IndexMap;
-use ndarray::ArrayView;
+use ndarray::{Array, ArrayD, ArrayView, ArrayViewD};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 
 /// Metadata associated with a dotThz measurement.
 #[derive(Default, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct DotthzMetaData {
     /// The user responsible for the measurement.
     pub user: String,
@@ -51,10 +54,26 @@ pub struct DotthzMetaData {
     pub date: String,
 }
 
+impl DotthzMetaData {
+    /// Look up the `dsDescription` entry of a dataset by dataset name.
+    ///
+    /// Entries are listed in order, so the n-th entry describes dataset `dsN`.
+    pub fn dataset_description(&self, dataset_name: &str) -> Option<&str> {
+        let index = dataset_name.strip_prefix("ds")?.parse::<usize>().ok()?;
+        self.ds_description
+            .get(index.checked_sub(1)?)
+            .map(String::as_str)
+    }
+}
+
 /// A structure representing a .thz file according to the dotThz standard
-pub struct DotthzFile {
-    /// contains the Group and Dataset names
-    file: File, // Keep a reference to the underlying HDF5 file
+///
+/// Groups, meta-data and `f64` datasets are accessed through the [`Backend`] `B`, so the
+/// dotThz logic can run on a [`MemoryBackend`](crate::MemoryBackend) as well. Typed and
+/// compressed datasets, dimension scales and SWMR access need the default [`Hdf5Backend`].
+pub struct DotthzFile<B = Hdf5Backend> {
+    /// storage of the groups, attributes and datasets
+    pub(crate) backend: B,
 }
 
 impl DotthzFile {
@@ -62,420 +81,706 @@ impl DotthzFile {
     pub fn create(path: &PathBuf) -> Result<Self, Box<dyn Error>> {
         // Create a new HDF5 file at the specified path
         let file = File::create(path)?;
-        Ok(Self { file })
+        Ok(Self::with_backend(file.into()))
     }
 
     /// Loads a `DotthzFile` from the specified path as read-only, file must exist.
     pub fn open(filename: &PathBuf) -> Result<Self, Box<dyn Error>> {
         let file = File::open(filename)?;
-        Ok(DotthzFile { file })
+        Ok(Self::with_backend(file.into()))
     }
 
     /// Opens a file as read/write, file must exist.
     pub fn open_rw<P: AsRef<Path>>(filename: P) -> Result<Self, Box<dyn Error>> {
         let file = File::open_rw(filename)?;
-        Ok(DotthzFile { file })
+        Ok(Self::with_backend(file.into()))
     }
 
     /// Creates a file, fails if exists.
     pub fn create_excl<P: AsRef<Path>>(filename: P) -> Result<Self, Box<dyn Error>> {
         let file = File::create_excl(filename)?;
-        Ok(DotthzFile { file })
+        Ok(Self::with_backend(file.into()))
     }
 
     /// Opens a file as read/write if exists, creates otherwise.
     pub fn append<P: AsRef<Path>>(filename: P) -> Result<Self, Box<dyn Error>> {
         let file = File::append(filename)?;
-        Ok(DotthzFile { file })
+        Ok(Self::with_backend(file.into()))
     }
 
     /// Opens a file in a given mode.
     pub fn open_as<P: AsRef<Path>>(filename: P, mode: OpenMode) -> Result<Self, Box<dyn Error>> {
         let file = File::open_as(filename, mode)?;
-        Ok(DotthzFile { file })
+        Ok(Self::with_backend(file.into()))
+    }
+
+    /// Create a file for single-writer/multiple-reader (SWMR) access, truncates if exists.
+    ///
+    /// Add all groups, meta-data and (extendable) datasets first, then call
+    /// [`DotthzFile::start_swmr`] before appending data.
+    pub fn create_swmr<P: AsRef<Path>>(filename: P) -> Result<Self, Box<dyn Error>> {
+        let file = File::with_options()
+            .with_fapl(|p| p.libver_latest())
+            .create(filename)?;
+        Ok(Self::with_backend(file.into()))
+    }
+
+    /// Switch a file created with [`DotthzFile::create_swmr`] to SWMR writing.
+    ///
+    /// Readers can open the file with [`DotthzFile::open_swmr`] afterwards. From now on no groups,
+    /// datasets or attributes can be added, only data written and datasets extended.
+    pub fn start_swmr(&self) -> Result<(), Box<dyn Error>> {
+        self.backend.file.start_swmr()?;
+        Ok(())
+    }
+
+    /// Opens a file as read-only while another process writes it in SWMR mode.
+    pub fn open_swmr<P: AsRef<Path>>(filename: P) -> Result<Self, Box<dyn Error>> {
+        Self::open_as(filename, OpenMode::ReadSWMR)
     }
 
     /// Returns the file size in bytes (or 0 if the file handle is invalid).
     pub fn size(&self) -> u64 {
-        self.file.size()
+        self.backend.file.size()
     }
 
     /// Returns the free space in the file in bytes (or 0 if the file handle is invalid).
     pub fn free_space(&self) -> u64 {
-        self.file.free_space()
+        self.backend.file.free_space()
     }
 
     /// Returns true if the file was opened in a read-only mode.
     pub fn is_read_only(&self) -> bool {
-        self.file.is_read_only()
+        self.backend.file.is_read_only()
     }
 
     /// Returns the userblock size in bytes (or 0 if the file handle is invalid).
     pub fn userblock(&self) -> u64 {
-        self.file.userblock()
+        self.backend.file.userblock()
     }
 
     /// Flushes the file to the storage medium.
     pub fn flush(&self) -> Result<(), Box<dyn Error>> {
-        self.file.flush()?;
+        self.backend.file.flush()?;
         Ok(())
     }
 
     /// Closes the file and invalidates all open handles for contained objects.
     pub fn close(self) -> Result<(), Box<dyn Error>> {
-        self.file.close()?;
+        self.backend.file.close()?;
         Ok(())
     }
 
     /// Returns a copy of the file access property list.
     pub fn access_plist(&self) -> hdf5::Result<FileAccess> {
-        self.file.access_plist()
+        self.backend.file.access_plist()
     }
 
     /// A short alias for `access_plist()`.
     pub fn fapl(&self) -> hdf5::Result<FileAccess> {
-        self.file.access_plist()
+        self.backend.file.access_plist()
     }
     /// Returns a copy of the file creation property list.
     pub fn create_plist(&self) -> hdf5::Result<FileCreate> {
-        self.file.create_plist()
+        self.backend.file.create_plist()
     }
 
     /// A short alias for `create_plist()`.
     pub fn fcpl(&self) -> hdf5::Result<FileCreate> {
-        self.file.create_plist()
-    }
-    /// get group names
-    pub fn get_group_names(&self) -> hdf5::Result<Vec<String>> {
-        Ok(self
-            .file
-            .groups()?
-            .iter()
-            .map(|s| s.name())
-            .collect::<Vec<String>>())
+        self.backend.file.create_plist()
     }
-
     /// get group by name
-    pub fn get_group(&self, group_name: &str) -> hdf5::Result<Group> {
-        self.file.group(group_name)
+    pub fn get_group(&self, group_name: &str) -> hdf5::Result<DotthzGroup> {
+        Ok(self.backend.file.group(group_name)?.into())
     }
 
     /// get groups
-    pub fn get_groups(&self) -> hdf5::Result<Vec<Group>> {
-        self.file.groups()
-    }
-
-    /// get dataset names for a given group name
-    pub fn get_dataset_names(&self, group_name: &str) -> hdf5::Result<Vec<String>> {
+    pub fn get_groups(&self) -> hdf5::Result<Vec<DotthzGroup>> {
         Ok(self
+            .backend
             .file
-            .group(group_name)?
-            .datasets()?
+            .groups()?
+            .into_iter()
+            .map(DotthzGroup::from)
+            .collect())
+    }
+
+    /// All measurement groups, including nested ones, see
+    /// [`DotthzFile::get_measurement_group_names`].
+    pub fn get_measurement_groups(&self) -> hdf5::Result<Vec<DotthzGroup>> {
+        self.get_measurement_group_names()?
             .iter()
-            .map(|d| d.name())
-            .collect::<Vec<String>>())
+            .map(|name| self.get_group(name))
+            .collect()
     }
 
     /// get dataset for a given group name by dataset name
     pub fn get_dataset(&self, group_name: &str, dataset_name: &str) -> hdf5::Result<Dataset> {
-        self.file.group(group_name)?.dataset(dataset_name)
+        self.backend.file.group(group_name)?.dataset(dataset_name)
     }
 
     /// get datasets for a given group name
     pub fn get_datasets(&self, group_name: &str) -> hdf5::Result<Vec<Dataset>> {
-        self.file.group(group_name)?.datasets()
+        self.backend.file.group(group_name)?.datasets()
     }
 
-    /// clear the meta-data for a given group
-    pub fn clear_meta_data(&self, group_name: &str) -> hdf5::Result<()> {
-        // delete all existing attributes
-        if let Ok(names) = self.get_group(group_name)?.attr_names() {
-            for attr in names {
-                self.get_group(group_name)?.delete_attr(&attr)?;
+    /// Read a dataset into an `ndarray` of element type `T` and dimension `D`.
+    ///
+    /// Values are converted if the stored type differs, as long as no information is lost apart
+    /// from rounding: between `f32` and `f64`, from integers to floats and to wider integers.
+    /// Use `IxDyn` as dimension to read datasets of any dimensionality.
+    pub fn read_dataset<T, D>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> Result<Array<T, D>, Box<dyn Error>>
+    where
+        T: H5Type,
+        D: ndarray::Dimension,
+    {
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        let stored = dataset.dtype()?.to_descriptor()?;
+        let requested = T::type_descriptor();
+        if !is_safe_conversion(&stored, &requested) {
+            return Err(format!(
+                "dataset `{}` is stored as {} and cannot be read as {}",
+                dataset_name, stored, requested
+            )
+            .into());
+        }
+        if let Some(ndim) = D::NDIM {
+            if ndim != dataset.ndim() {
+                return Err(format!(
+                    "dataset `{}` has {} dimensions (shape {:?}), but {} were requested",
+                    dataset_name,
+                    dataset.ndim(),
+                    dataset.shape(),
+                    ndim
+                )
+                .into());
             }
         }
+        Ok(dataset.read::<T, D>()?)
+    }
+
+    /// Add a group with meta-data and group name to the `DotthzFile`.
+    ///
+    /// The name may be a path like `campaign/sample/300K`, missing parent groups are created
+    /// without meta-data.
+    pub fn add_group(
+        &mut self,
+        group_name: &str,
+        metadata: &DotthzMetaData,
+    ) -> Result<DotthzGroup, Box<dyn Error>> {
+        self.create_group(group_name, metadata)?;
+        Ok(self.get_group(group_name)?)
+    }
+
+    /// Add a dataset to a given group by group name and dataset name.
+    pub fn add_dataset<T, D>(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dataset: ArrayView<'_, T, D>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: H5Type + Debug,
+        D: ndarray::Dimension, // Ensure dimensions are compatible with HDF5
+    {
+        self.add_dataset_with(
+            group_name,
+            dataset_name,
+            dataset,
+            &DatasetOptions::default(),
+        )
+    }
+
+    /// Add a dataset with the given chunking and compression options.
+    ///
+    /// If a filter is set but no chunk shape, the chunk shape is chosen with
+    /// [`DatasetOptions::auto_chunk`].
+    pub fn add_dataset_with<T, D>(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dataset: ArrayView<'_, T, D>,
+        options: &DatasetOptions,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: H5Type + Debug,
+        D: ndarray::Dimension,
+    {
+        self.get_group(group_name)?
+            .add_dataset_with(dataset_name, dataset, options)?;
         Ok(())
     }
 
-    /// set meta-data for a given group
-    pub fn set_meta_data(
-        &self,
-        group: &mut Group,
-        meta_data: &DotthzMetaData,
-    ) -> Result<(), Box<dyn Error>> {
-        // Save metadata attributes, create if they don't already exist
-        if let Ok(attr) = group.attr("description") {
-            attr.write_scalar(&VarLenUnicode::from_str(&meta_data.description)?)?;
-        } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("description")?
-                .write_scalar(&VarLenUnicode::from_str(&meta_data.description)?)?;
+    /// Replace the data of an existing dataset, keeping its `dsDescription` entry.
+    ///
+    /// Data of the same shape and type is written in place. Otherwise the dataset is created
+    /// again with its attributes, and its axes are attached again where their length still fits.
+    pub fn replace_dataset<T, D>(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dataset: ArrayView<'_, T, D>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: H5Type + Debug,
+        D: ndarray::Dimension,
+    {
+        let group = self.get_group(group_name)?;
+        if !group.link_exists(dataset_name) {
+            return Err(format!(
+                "dataset `{}` does not exist in group `{}`",
+                dataset_name, group_name
+            )
+            .into());
         }
-
-        if let Ok(attr) = group.attr("date") {
-            attr.write_scalar(&VarLenUnicode::from_str(&meta_data.date)?)?;
-        } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("date")?
-                .write_scalar(&VarLenUnicode::from_str(&meta_data.date)?)?;
+        let existing = group.dataset(dataset_name)?;
+        if existing.shape() == dataset.shape() && existing.dtype()? == Datatype::from_type::<T>()? {
+            existing.write(dataset)?;
+            return Ok(());
         }
 
-        if let Ok(attr) = group.attr("instrument") {
-            attr.write_scalar(&VarLenUnicode::from_str(&meta_data.instrument)?)?;
-        } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("instrument")?
-                .write_scalar(&VarLenUnicode::from_str(&meta_data.instrument)?)?;
+        let attributes = self.get_dataset_attributes(group_name, dataset_name)?;
+        let scale_name = is_dimension_scale(&existing).then(|| {
+            existing
+                .attr("NAME")
+                .and_then(|attr| attr.read_scalar::<VarLenUnicode>())
+                .map_or_else(|_| dataset_name.to_string(), |name| name.to_string())
+        });
+        let (axes, attached) = detach_scales(&existing)?;
+        group.unlink(dataset_name)?;
+        self.add_dataset(group_name, dataset_name, dataset)?;
+        let replaced = group.dataset(dataset_name)?;
+        if !attributes.is_empty() {
+            self.set_dataset_attributes(group_name, dataset_name, &attributes)?;
         }
-
-        if let Ok(attr) = group.attr("mode") {
-            attr.write_scalar(&VarLenUnicode::from_str(&meta_data.mode)?)?;
-        } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("mode")?
-                .write_scalar(&VarLenUnicode::from_str(&meta_data.mode)?)?;
+        if let Some(name) = scale_name {
+            mark_dimension_scale(&replaced, &name)?;
+            for (target, dim) in attached {
+                if replaced.ndim() == 1 && target.shape().get(dim) == Some(&replaced.size()) {
+                    attach_scale(&target, dim, &replaced)?;
+                }
+            }
         }
-
-        if let Ok(attr) = group.attr("thzVer") {
-            attr.write_scalar(&VarLenUnicode::from_str(&meta_data.version)?)?;
-        } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("thzVer")?
-                .write_scalar(&VarLenUnicode::from_str(&meta_data.version)?)?;
+        for (dim, axes) in axes.into_iter().enumerate() {
+            for axis in axes {
+                if replaced.shape().get(dim) == Some(&axis.size()) {
+                    attach_scale(&replaced, dim, &axis)?;
+                }
+            }
         }
+        Ok(())
+    }
 
-        if let Ok(attr) = group.attr("time") {
-            attr.write_scalar(&VarLenUnicode::from_str(&meta_data.time)?)?;
+    /// Add a dataset, or replace it if it exists, and set its `dsDescription` entry.
+    ///
+    /// `dsDescription` lists the descriptions of `ds1` to `dsN` in order, so the dataset must be
+    /// one of them or the next one after the highest, `dsN+1`. A new dataset takes the place of
+    /// an entry that was filled in before the dataset existed. Existing datasets are replaced as
+    /// by [`DotthzFile::replace_dataset`], no other dataset is renamed.
+    pub fn upsert_dataset<T, D>(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dataset: ArrayView<'_, T, D>,
+        description: &str,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: H5Type + Debug,
+        D: ndarray::Dimension,
+    {
+        let exists = self.get_group(group_name)?.link_exists(dataset_name);
+        let last = self.last_ds_index(group_name)?;
+        let n = match ds_index(dataset_name).filter(|&n| n > 0) {
+            Some(n) if exists || n == last + 1 => n,
+            _ => {
+                return Err(format!(
+                    "`dsDescription` describes `ds1` to `dsN` in order, the next dataset of \
+                     group `{}` is `ds{}`, not `{}`",
+                    group_name,
+                    last + 1,
+                    dataset_name
+                )
+                .into())
+            }
+        };
+        if exists {
+            self.replace_dataset(group_name, dataset_name, dataset)?;
         } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("time")?
-                .write_scalar(&VarLenUnicode::from_str(&meta_data.time)?)?;
+            self.add_dataset(group_name, dataset_name, dataset)?;
         }
+        self.set_ds_description(group_name, n, description)
+    }
 
-        let entry = VarLenUnicode::from_str(
-            format!(
-                "{}/{}/{}/{}",
-                meta_data.orcid, meta_data.user, meta_data.email, meta_data.institution
-            )
-            .as_str(),
-        )
-        .unwrap();
+    /// Add a dataset under the next free `dsN` name and append its description to
+    /// `dsDescription`, returning the name of the new dataset.
+    ///
+    /// The name follows the highest existing `dsN`, gaps in the numbering are not filled and no
+    /// other dataset is renamed.
+    pub fn add_described_dataset<T, D>(
+        &mut self,
+        group_name: &str,
+        description: &str,
+        dataset: ArrayView<'_, T, D>,
+    ) -> Result<String, Box<dyn Error>>
+    where
+        T: H5Type + Debug,
+        D: ndarray::Dimension,
+    {
+        let dataset_name = format!("ds{}", self.last_ds_index(group_name)? + 1);
+        self.upsert_dataset(group_name, &dataset_name, dataset, description)?;
+        Ok(dataset_name)
+    }
 
-        if let Ok(attr) = group.attr("user") {
-            attr.write_scalar(&entry)?;
-        } else {
-            let attr = group.new_attr::<VarLenUnicode>().create("user")?;
-            attr.write_scalar(&entry)?;
+    /// Get the first dataset of a group whose `dsDescription` entry matches `description`.
+    pub fn get_dataset_by_description(
+        &self,
+        group_name: &str,
+        description: &str,
+    ) -> Result<Dataset, Box<dyn Error>> {
+        let group = self.get_group(group_name)?;
+        // the n-th entry describes `dsN`, skip entries filled in before their dataset exists
+        let dataset_name = self
+            .get_meta_data(group_name)?
+            .ds_description
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (format!("ds{}", i + 1), entry))
+            .find(|(name, entry)| *entry == description && group.link_exists(name))
+            .map(|(name, _)| name)
+            .ok_or_else(|| {
+                format!(
+                    "group `{}` has no dataset described as `{}`",
+                    group_name, description
+                )
+            })?;
+        Ok(self.get_dataset(group_name, &dataset_name)?)
+    }
+
+    /// Delete a dataset, detaching it from its axes.
+    ///
+    /// No other dataset is renamed. The `dsDescription` entry of a `dsN` dataset is removed if
+    /// it is the last entry, otherwise it is kept so the following datasets stay described; use
+    /// [`DotthzFile::renumber_datasets`] to close the gap.
+    pub fn delete_dataset(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let group = self.get_group(group_name)?;
+        detach_scales(&group.dataset(dataset_name)?)?;
+        group.unlink(dataset_name)?;
+        let mut ds_description = self.get_meta_data(group_name)?.ds_description;
+        if ds_index(dataset_name).is_some_and(|n| n > 0 && n == ds_description.len()) {
+            ds_description.pop();
+            self.write_ds_description(group_name, ds_description)?;
         }
+        Ok(())
+    }
 
-        // Save additional metadata
-        let md_descriptions = meta_data
-            .md
-            .keys()
-            .cloned()
-            .collect::<Vec<String>>()
-            .join(", ");
+    /// Rename the `dsN` datasets of a group to `ds1`, `ds2`, ... in order, closing gaps left by
+    /// [`DotthzFile::delete_dataset`] or [`DotthzFile::rename_dataset`], and drop the
+    /// `dsDescription` entries of the missing datasets.
+    pub fn renumber_datasets(&mut self, group_name: &str) -> Result<(), Box<dyn Error>> {
+        let (datasets, pending) = self.dataset_sequence(group_name)?;
+        self.write_dataset_sequence(group_name, datasets, pending)
+    }
 
-        if let Ok(attr) = group.attr("mdDescription") {
-            attr.write_raw(&[VarLenUnicode::from_str(&md_descriptions)?])?;
-        } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("mdDescription")?
-                .write_raw(&[VarLenUnicode::from_str(&md_descriptions)?])?;
+    /// Rename a dataset, keeping `dsDescription` in sync.
+    ///
+    /// Attached axes stay attached and no other dataset is renamed. A dataset can only be
+    /// renamed to the `dsN` name after the highest one. A `dsN` dataset keeps its description,
+    /// others are described by their previous name unless an entry was filled in before. A `dsN`
+    /// dataset renamed to another name loses its entry if it is the last one, see
+    /// [`DotthzFile::delete_dataset`].
+    pub fn rename_dataset(
+        &mut self,
+        group_name: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let group = self.get_group(group_name)?;
+        if !group.link_exists(from) {
+            return Err(format!(
+                "dataset `{}` does not exist in group `{}`",
+                from, group_name
+            )
+            .into());
         }
-
-        for (i, (_key, value)) in meta_data.md.iter().enumerate() {
-            if let Ok(attr) = group.attr(format!("md{}", i + 1).as_str()) {
-                if let Ok(parsed) = value.parse::<f32>() {
-                    attr.write_scalar(&parsed)?;
-                } else {
-                    attr.write_scalar(&VarLenUnicode::from_str(value)?)?;
-                }
-            } else {
-                group
-                    .new_attr::<VarLenUnicode>()
-                    .create(format!("md{}", i + 1).as_str())?
-                    .write_scalar(&VarLenUnicode::from_str(value)?)?;
+        if group.link_exists(to) {
+            return Err(
+                format!("dataset `{}` already exists in group `{}`", to, group_name).into(),
+            );
+        }
+        let mut ds_description = self.get_meta_data(group_name)?.ds_description;
+        let from_index = ds_index(from).filter(|&n| n > 0);
+        if let Some(n) = ds_index(to) {
+            let last = self.last_ds_index(group_name)?;
+            if n != last + 1 {
+                return Err(format!(
+                    "`dsDescription` describes `ds1` to `dsN` in order, the next dataset of \
+                     group `{}` is `ds{}`, not `{}`",
+                    group_name,
+                    last + 1,
+                    to
+                )
+                .into());
+            }
+            let previous = from_index.and_then(|i| ds_description.get(i - 1).cloned());
+            if ds_description.len() < n {
+                pad_ds_description(&mut ds_description, n - 1);
+                ds_description.push(previous.unwrap_or_else(|| from.to_string()));
+            } else if let Some(previous) = previous {
+                ds_description[n - 1] = previous;
             }
         }
+        if from_index.is_some_and(|n| n == ds_description.len()) {
+            ds_description.pop();
+        }
+        group.relink(from, to)?;
+        self.write_ds_description(group_name, ds_description)
+    }
 
-        // Save dsDescription
-        let ds_descriptions = meta_data.ds_description.join(", ");
-
-        if let Ok(attr) = group.attr("dsDescription") {
-            attr.write_raw(&[VarLenUnicode::from_str(&ds_descriptions)?])?;
+    /// Add a group, or update the meta-data of an existing group.
+    pub fn upsert_group(
+        &mut self,
+        group_name: &str,
+        metadata: &DotthzMetaData,
+    ) -> Result<DotthzGroup, Box<dyn Error>> {
+        if self.backend.file.link_exists(group_name) {
+            self.set_meta_data(group_name, metadata)?;
+            Ok(self.get_group(group_name)?)
         } else {
-            group
-                .new_attr::<VarLenUnicode>()
-                .create("dsDescription")?
-                .write_raw(&[VarLenUnicode::from_str(&ds_descriptions)?])?;
+            self.add_group(group_name, metadata)
         }
+    }
+
+    /// Rename a group.
+    pub fn rename_group(&mut self, from: &str, to: &str) -> Result<(), Box<dyn Error>> {
+        if self.backend.file.link_exists(to) {
+            return Err(format!("group `{}` already exists", to).into());
+        }
+        self.create_parent_groups(to)?;
+        self.backend.file.relink(from, to)?;
         Ok(())
     }
 
-    /// extract meta-data for a given group by group name
-    pub fn get_meta_data(&self, group_name: &str) -> hdf5::Result<DotthzMetaData> {
-        let mut meta_data = DotthzMetaData::default();
+    /// The highest `N` of the `dsN` datasets of a group, 0 if there are none.
+    fn last_ds_index(&self, group_name: &str) -> Result<usize, Box<dyn Error>> {
+        Ok(self
+            .get_dataset_names(group_name)?
+            .iter()
+            .filter_map(|name| ds_index(name.rsplit('/').next().unwrap_or(name)))
+            .max()
+            .unwrap_or(0))
+    }
 
-        if let Ok(instrument) = self
-            .file
-            .group(group_name)?
-            .attr("instrument")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            if let Some(d) = instrument.first() {
-                meta_data.instrument = d.to_string();
-            }
-        }
+    /// Set the `dsDescription` entry of `dsN`, describing missing datasets before it by name.
+    fn set_ds_description(
+        &mut self,
+        group_name: &str,
+        n: usize,
+        description: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut ds_description = self.get_meta_data(group_name)?.ds_description;
+        pad_ds_description(&mut ds_description, n);
+        ds_description[n - 1] = description.to_string();
+        self.write_ds_description(group_name, ds_description)
+    }
 
-        // Load dataset descriptions
-        if let Ok(ds_description) = self
-            .file
-            .group(group_name)?
-            .attr("dsDescription")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            let descriptions: Vec<String> = ds_description
-                .iter()
-                .flat_map(|s| s.split(", ").map(String::from).collect::<Vec<String>>())
-                .collect();
-            meta_data.ds_description = descriptions;
-        }
+    /// Replace the `dsDescription` of a group, keeping the other meta-data.
+    fn write_ds_description(
+        &mut self,
+        group_name: &str,
+        ds_description: Vec<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut meta_data = self.get_meta_data(group_name)?;
+        meta_data.ds_description = ds_description;
+        self.set_meta_data(group_name, &meta_data)
+    }
 
-        if let Ok(md_description) = self
-            .file
-            .group(group_name)?
-            .attr("mdDescription")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            // Convert ds_description to a vector of strings, splitting any single entry by ", "
-            let descriptions: Vec<String> = if md_description.len() == 1 {
-                // If there's only one entry, split it by ", "
-                md_description[0]
-                    .split(", ")
-                    .map(|s| s.to_string())
-                    .collect()
-            } else {
-                // Otherwise, assume it's already in the correct format
-                md_description.iter().map(|s| s.to_string()).collect()
-            };
-
-            for (i, description) in descriptions.iter().enumerate() {
-                // now read the mds
-                if let Ok(md) = self
-                    .file
-                    .group(group_name)?
-                    .attr(format!("md{}", i + 1).as_str())
-                    .and_then(|a| a.read_raw::<f32>())
-                {
-                    if let Some(md) = md.first() {
-                        meta_data
-                            .md
-                            .insert(description.to_string(), format!("{}", md));
-                    }
-                }
-                if let Ok(md) = self
-                    .file
-                    .group(group_name)?
-                    .attr(format!("md{}", i + 1).as_str())
-                    .and_then(|a| a.read_raw::<VarLenUnicode>())
-                {
-                    if let Some(md) = md.first() {
-                        meta_data
-                            .md
-                            .insert(description.to_string(), format!("{}", md));
-                    }
-                }
-            }
-        }
+    /// The `dsN` datasets of a group in order with their `dsDescription` entry, falling back to
+    /// the dataset name, and the entries following the last dataset.
+    fn dataset_sequence(&self, group_name: &str) -> Result<DatasetSequence, Box<dyn Error>> {
+        let meta_data = self.get_meta_data(group_name)?;
+        let mut names = self
+            .get_dataset_names(group_name)?
+            .iter()
+            .map(|name| name.rsplit('/').next().unwrap_or(name).to_string())
+            .filter_map(|name| Some((ds_index(&name).filter(|&n| n > 0)?, name)))
+            .collect::<Vec<(usize, String)>>();
+        names.sort();
+        let last = names.last().map_or(0, |(n, _)| *n);
+        let datasets = names
+            .into_iter()
+            .map(|(n, name)| {
+                let description = meta_data.ds_description.get(n - 1).cloned();
+                (name.clone(), description.unwrap_or(name))
+            })
+            .collect();
+        let pending = meta_data
+            .ds_description
+            .get(last..)
+            .unwrap_or_default()
+            .to_vec();
+        Ok((datasets, pending))
+    }
 
-        if let Ok(mode) = self
-            .file
-            .group(group_name)?
-            .attr("mode")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            if let Some(d) = mode.first() {
-                meta_data.mode = d.to_string();
-            }
+    /// Rename the given datasets to `ds1`, `ds2`, ... in order and write their descriptions to
+    /// `dsDescription`, followed by the `pending` entries of datasets that do not exist yet.
+    fn write_dataset_sequence(
+        &mut self,
+        group_name: &str,
+        datasets: Vec<(String, String)>,
+        pending: Vec<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        let group = self.get_group(group_name)?;
+        let moves = datasets
+            .iter()
+            .enumerate()
+            .filter(|(i, (name, _))| *name != format!("ds{}", i + 1))
+            .map(|(i, (name, _))| (i, name.as_str()))
+            .collect::<Vec<(usize, &str)>>();
+        // move the datasets out of the way first, so renaming never collides
+        for (i, name) in moves.iter() {
+            group.relink(name, &format!(".renumber{}", i + 1))?;
         }
-
-        if let Ok(version) = self
-            .file
-            .group(group_name)?
-            .attr("thzVer")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            if let Some(d) = version.first() {
-                meta_data.version = d.to_string();
-            }
+        for (i, _) in moves.iter() {
+            group.relink(&format!(".renumber{}", i + 1), &format!("ds{}", i + 1))?;
         }
 
-        if let Ok(description) = self
-            .file
-            .group(group_name)?
-            .attr("description")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            if let Some(d) = description.first() {
-                meta_data.description = d.to_string();
-            }
-        }
+        let ds_description = datasets
+            .into_iter()
+            .map(|(_, description)| description)
+            .chain(pending)
+            .collect();
+        self.write_ds_description(group_name, ds_description)
+    }
+}
 
-        if let Ok(time) = self
-            .file
-            .group(group_name)?
-            .attr("time")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            if let Some(d) = time.first() {
-                meta_data.time = d.to_string();
-            }
-        }
+impl<B: Backend> DotthzFile<B> {
+    /// A `DotthzFile` stored in the given backend, e.g. a [`MemoryBackend`](crate::MemoryBackend).
+    pub fn with_backend(backend: B) -> Self {
+        DotthzFile { backend }
+    }
 
-        if let Ok(date) = self
-            .file
-            .group(group_name)?
-            .attr("date")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            if let Some(d) = date.first() {
-                meta_data.date = d.to_string();
+    /// The storage backend of the file.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// The storage backend of the file, to access it directly.
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// The storage backend, consuming the file.
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
+
+    /// get group names
+    pub fn get_group_names(&self) -> hdf5::Result<Vec<String>> {
+        self.backend.group_names("/").map_err(to_hdf5_error)
+    }
+
+    /// Full paths of all measurement groups, including nested ones, e.g. `/campaign/sample/300K`.
+    ///
+    /// Unlike [`DotthzFile::get_group_names`], which only lists top-level groups, the whole file
+    /// is traversed. A measurement group is a group holding datasets or a group without
+    /// subgroups; groups that only organise other groups, like `campaign` and `sample` above, are
+    /// skipped.
+    pub fn get_measurement_group_names(&self) -> hdf5::Result<Vec<String>> {
+        let mut names = Vec::new();
+        collect_measurement_group_names(&self.backend, "/", &mut names).map_err(to_hdf5_error)?;
+        Ok(names)
+    }
+
+    /// get dataset names for a given group name
+    pub fn get_dataset_names(&self, group_name: &str) -> hdf5::Result<Vec<String>> {
+        Ok(self
+            .backend
+            .dataset_names(group_name)
+            .map_err(to_hdf5_error)?
+            .iter()
+            .map(|name| dataset_path(group_name, name))
+            .collect::<Vec<String>>())
+    }
+
+    /// Read an `f64` dataset of any dimensionality through the backend.
+    ///
+    /// Use [`DotthzFile::read_dataset`] to read other types from an HDF5 file.
+    pub fn read_f64_dataset(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> Result<ArrayD<f64>, Box<dyn Error>> {
+        self.backend.read_dataset(group_name, dataset_name)
+    }
+
+    /// Add an `f64` dataset to a given group through the backend.
+    ///
+    /// Use [`DotthzFile::add_dataset`] for other types, compression and dimension scales.
+    pub fn add_f64_dataset(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dataset: ArrayViewD<'_, f64>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.backend
+            .write_dataset(group_name, dataset_name, dataset)
+    }
+
+    /// clear the meta-data for a given group
+    pub fn clear_meta_data(&mut self, group_name: &str) -> hdf5::Result<()> {
+        // delete all existing attributes
+        if let Ok(names) = self.backend.attr_names(group_name) {
+            for attr in names {
+                self.backend
+                    .delete_attr(group_name, &attr)
+                    .map_err(to_hdf5_error)?;
             }
         }
+        Ok(())
+    }
 
-        if let Ok(user_info) = self
-            .file
-            .group(group_name)?
-            .attr("user")
-            .and_then(|a| a.read_raw::<VarLenUnicode>())
-        {
-            if let Some(d) = user_info.first() {
-                let user_info_str = d.to_string();
-                let user_parts: Vec<&str> = user_info_str.split('/').collect();
-
-                // Check each part individually to handle cases where fewer than 4 parts are available
-                if let Some(part) = user_parts.first() {
-                    meta_data.orcid = part.trim().into();
-                }
-                if let Some(part) = user_parts.get(1) {
-                    meta_data.user = part.trim().into();
-                }
-                if let Some(part) = user_parts.get(2) {
-                    meta_data.email = part.trim().into();
-                }
-                if let Some(part) = user_parts.get(3) {
-                    meta_data.institution = part.trim().into();
-                }
-            }
+    /// Replace the meta-data of a given group, see [`DotthzGroup::set_meta_data`].
+    pub fn set_meta_data(
+        &mut self,
+        group_name: &str,
+        meta_data: &DotthzMetaData,
+    ) -> Result<(), Box<dyn Error>> {
+        self.backend.update_meta_data(group_name, meta_data)
+    }
+
+    /// extract meta-data for a given group by group name
+    pub fn get_meta_data(&self, group_name: &str) -> hdf5::Result<DotthzMetaData> {
+        self.backend
+            .get_meta_data(group_name)
+            .map_err(to_hdf5_error)
+    }
+
+    /// Meta-data of a group combined with the meta-data of the groups above it.
+    ///
+    /// Nested layouts such as `campaign/sample/300K` can store common meta-data once in a parent
+    /// group. Going from the root group down to `group_name`, each group overrides what its
+    /// parents set:
+    ///
+    /// * text fields (`user`, `description`, `mode`, `instrument`, `date`, ...) take the value of
+    ///   the innermost group where they are not empty,
+    /// * `md` entries are merged, inner groups override entries with the same key,
+    /// * `dsDescription` describes the datasets of a group and is never inherited.
+    pub fn get_inherited_meta_data(&self, group_name: &str) -> hdf5::Result<DotthzMetaData> {
+        let mut meta_data = self.get_meta_data("/")?;
+        let mut path = String::new();
+        for component in group_name.split('/').filter(|c| !c.is_empty()) {
+            path = format!("{}/{}", path, component);
+            inherit_meta_data(&mut meta_data, self.get_meta_data(&path)?);
         }
+        meta_data.ds_description = self.get_meta_data(group_name)?.ds_description;
         Ok(meta_data)
     }
 
@@ -485,42 +790,136 @@ impl DotthzFile {
         group_name: &str,
         attr_name: &str,
     ) -> hdf5::Result<()> {
-        self.file.group(group_name)?.delete_attr(attr_name)
+        self.backend
+            .delete_attr(group_name, attr_name)
+            .map_err(to_hdf5_error)
     }
 
-    /// Add a group with meta-data and group name to the `DotthzFile`.
-    pub fn add_group(
+    /// replace the meta-data of a given group, removing `mdN` attributes that are no longer used
+    #[deprecated(since = "0.4.0", note = "use `set_meta_data`, which behaves the same")]
+    pub fn update_meta_data(
         &mut self,
         group_name: &str,
-        metadata: &DotthzMetaData,
-    ) -> Result<Group, Box<dyn Error>> {
-        let mut group = self.file.create_group(group_name)?;
-        self.set_meta_data(&mut group, metadata)?;
-        Ok(group)
+        meta_data: &DotthzMetaData,
+    ) -> Result<(), Box<dyn Error>> {
+        self.set_meta_data(group_name, meta_data)
     }
 
-    /// Add a dataset to a given group by group name and dataset name.
-    pub fn add_dataset<T, D>(
+    /// Create a group with meta-data, like [`DotthzFile::add_group`] for any backend.
+    ///
+    /// The name may be a path like `campaign/sample/300K`, missing parent groups are created
+    /// without meta-data.
+    pub fn create_group(
         &mut self,
         group_name: &str,
-        dataset_name: &str,
-        dataset: ArrayView<'_, T, D>,
-    ) -> Result<(), Box<dyn Error>>
-    where
-        T: H5Type + Debug,
-        D: ndarray::Dimension, // Ensure dimensions are compatible with HDF5
-    {
-        // Retrieve or create the group
-        let group = self.file.group(group_name)?;
-        // Create the dataset in the specified group with the shape from the ndarray
-        let ds = group
-            .new_dataset::<T>()
-            .shape(dataset.shape())
-            .create(dataset_name)?;
+        metadata: &DotthzMetaData,
+    ) -> Result<(), Box<dyn Error>> {
+        self.create_parent_groups(group_name)?;
+        self.backend.create_group(group_name)?;
+        self.backend.set_meta_data(group_name, metadata)
+    }
 
-        // Write the data into the dataset
-        ds.write(dataset)?;
+    /// Delete a group with all its datasets.
+    pub fn delete_group(&mut self, group_name: &str) -> Result<(), Box<dyn Error>> {
+        self.backend.delete_group(group_name)
+    }
 
+    /// Create the missing parents of a group path, e.g. `campaign` and `campaign/sample` for
+    /// `campaign/sample/300K`.
+    fn create_parent_groups(&mut self, group_name: &str) -> Result<(), Box<dyn Error>> {
+        let components = group_name.split('/').collect::<Vec<&str>>();
+        for i in 1..components.len() {
+            let parent = components[..i].join("/");
+            if !parent.is_empty() && !self.backend.has_group(&parent) {
+                self.backend.create_group(&parent)?;
+            }
+        }
         Ok(())
     }
 }
+
+/// `dsN` datasets with their descriptions, and the entries following the last dataset.
+type DatasetSequence = (Vec<(String, String)>, Vec<String>);
+
+/// Whether values stored as `stored` can be read as `requested` without losing more than
+/// floating point precision.
+fn is_safe_conversion(stored: &TypeDescriptor, requested: &TypeDescriptor) -> bool {
+    use TypeDescriptor::{Float, Integer, Unsigned};
+    match (stored, requested) {
+        _ if stored == requested => true,
+        (Float(_) | Integer(_) | Unsigned(_), Float(_)) => true,
+        (Integer(from), Integer(to)) | (Unsigned(from), Unsigned(to)) => from <= to,
+        (Unsigned(from), Integer(to)) => from < to,
+        _ => false,
+    }
+}
+
+/// Extend `dsDescription` to at least `len` entries, describing `dsN` by its name.
+fn pad_ds_description(ds_description: &mut Vec<String>, len: usize) {
+    while ds_description.len() < len {
+        ds_description.push(format!("ds{}", ds_description.len() + 1));
+    }
+}
+
+/// The number `N` of a dataset named `dsN`.
+pub(crate) fn ds_index(dataset_name: &str) -> Option<usize> {
+    dataset_name.strip_prefix("ds")?.parse().ok()
+}
+
+/// Collect the paths of the measurement groups below `group` depth-first, see
+/// [`DotthzFile::get_measurement_group_names`].
+fn collect_measurement_group_names<B: Backend>(
+    backend: &B,
+    group: &str,
+    names: &mut Vec<String>,
+) -> Result<(), Box<dyn Error>> {
+    for child in backend.group_names(group)? {
+        if backend.group_names(&child)?.is_empty() || !backend.dataset_names(&child)?.is_empty() {
+            names.push(child.clone());
+        }
+        collect_measurement_group_names(backend, &child, names)?;
+    }
+    Ok(())
+}
+
+/// The full path of a dataset, e.g. `/Measurement/ds1`.
+fn dataset_path(group_name: &str, dataset_name: &str) -> String {
+    let components = group_name
+        .split('/')
+        .filter(|c| !c.is_empty())
+        .chain([dataset_name])
+        .collect::<Vec<&str>>();
+    format!("/{}", components.join("/"))
+}
+
+/// Backend errors as HDF5 errors, for methods returning [`hdf5::Result`].
+fn to_hdf5_error(e: Box<dyn Error>) -> hdf5::Error {
+    hdf5::Error::from(e.to_string())
+}
+
+/// Whether a group holds a measurement, i.e. it holds datasets or has no subgroups.
+pub(crate) fn is_measurement_group(group: &Group, subgroups: &[Group]) -> hdf5::Result<bool> {
+    Ok(subgroups.is_empty() || !group.datasets()?.is_empty())
+}
+
+/// Override the fields of `inherited` that are set in `meta_data`.
+pub(crate) fn inherit_meta_data(inherited: &mut DotthzMetaData, meta_data: DotthzMetaData) {
+    let fields = [
+        (&mut inherited.user, meta_data.user),
+        (&mut inherited.email, meta_data.email),
+        (&mut inherited.orcid, meta_data.orcid),
+        (&mut inherited.institution, meta_data.institution),
+        (&mut inherited.description, meta_data.description),
+        (&mut inherited.version, meta_data.version),
+        (&mut inherited.mode, meta_data.mode),
+        (&mut inherited.instrument, meta_data.instrument),
+        (&mut inherited.time, meta_data.time),
+        (&mut inherited.date, meta_data.date),
+    ];
+    for (field, value) in fields {
+        if !value.is_empty() {
+            *field = value;
+        }
+    }
+    inherited.md.extend(meta_data.md);
+}
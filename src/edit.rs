@@ -0,0 +1,226 @@
+use crate::DotthzMetaData;
+use std::error::Error;
+use std::fmt;
+
+/// Names accepted for the fixed meta-data fields, mapped to the attribute they are stored in.
+///
+/// Any other key refers to an entry of `md`. Prefix a key with `md:` to address an `md` entry
+/// whose name collides with one of these fields.
+const FIELDS: [(&str, &str); 11] = [
+    ("description", "description"),
+    ("date", "date"),
+    ("time", "time"),
+    ("mode", "mode"),
+    ("instrument", "instrument"),
+    ("version", "thzVer"),
+    ("thzVer", "thzVer"),
+    ("user", "user"),
+    ("email", "email"),
+    ("orcid", "orcid"),
+    ("institution", "institution"),
+];
+
+/// A single change to the meta-data of a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaDataEdit {
+    /// Set a field or `md` entry, adding the entry if it does not exist.
+    Set {
+        /// Field name or `md` key.
+        key: String,
+        /// New value.
+        value: String,
+    },
+    /// Clear a field or remove an `md` entry.
+    Unset {
+        /// Field name or `md` key.
+        key: String,
+    },
+    /// Rename an `md` entry, keeping its value and position.
+    Rename {
+        /// Current `md` key.
+        from: String,
+        /// New `md` key.
+        to: String,
+    },
+}
+
+impl MetaDataEdit {
+    /// Apply the edit to `meta_data`.
+    ///
+    /// Unsetting or renaming an `md` entry that does not exist is an error, so typos in batch
+    /// edits do not go unnoticed. `md` keys containing `", "` are rejected, as that separates
+    /// the keys in `mdDescription`.
+    pub fn apply(&self, meta_data: &mut DotthzMetaData) -> Result<(), Box<dyn Error>> {
+        match self {
+            MetaDataEdit::Set { key, value } => match field(key) {
+                Some(field) => *field_mut(meta_data, field) = value.clone(),
+                None => {
+                    check_md_key(md_key(key))?;
+                    meta_data.md.insert(md_key(key).to_string(), value.clone());
+                }
+            },
+            MetaDataEdit::Unset { key } => match field(key) {
+                Some(field) => field_mut(meta_data, field).clear(),
+                None => {
+                    meta_data
+                        .md
+                        .shift_remove(md_key(key))
+                        .ok_or_else(|| format!("md entry `{}` does not exist", md_key(key)))?;
+                }
+            },
+            MetaDataEdit::Rename { from, to } => {
+                let (from, to) = (md_key(from), md_key(to));
+                let index = meta_data
+                    .md
+                    .get_index_of(from)
+                    .ok_or_else(|| format!("md entry `{}` does not exist", from))?;
+                if from == to {
+                    return Ok(());
+                }
+                check_md_key(to)?;
+                if meta_data.md.contains_key(to) {
+                    return Err(format!("md entry `{}` already exists", to).into());
+                }
+                let (_, value) = meta_data.md.shift_remove_index(index).unwrap();
+                meta_data.md.shift_insert(index, to.to_string(), value);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for MetaDataEdit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaDataEdit::Set { key, value } => write!(f, "set {}={}", key, value),
+            MetaDataEdit::Unset { key } => write!(f, "unset {}", key),
+            MetaDataEdit::Rename { from, to } => write!(f, "rename {}={}", from, to),
+        }
+    }
+}
+
+/// A difference between two sets of meta-data, as produced by [`diff_meta_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaDataChange {
+    /// Field name, or `md:<key>` for `md` entries.
+    pub key: String,
+    /// Value before the change, `None` if the entry was added.
+    pub old: Option<String>,
+    /// Value after the change, `None` if the entry was removed.
+    pub new: Option<String>,
+}
+
+impl fmt::Display for MetaDataChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(old) = &self.old {
+            write!(f, "- {}: {}", self.key, old)?;
+            if self.new.is_some() {
+                writeln!(f)?;
+            }
+        }
+        if let Some(new) = &self.new {
+            write!(f, "+ {}: {}", self.key, new)?;
+        }
+        Ok(())
+    }
+}
+
+/// List the fields and `md` entries that differ between `old` and `new`.
+pub fn diff_meta_data(old: &DotthzMetaData, new: &DotthzMetaData) -> Vec<MetaDataChange> {
+    let mut changes = Vec::new();
+    for (name, attr) in FIELDS.iter() {
+        // `version` and `thzVer` are aliases, only report them once
+        if *name == "thzVer" {
+            continue;
+        }
+        let (old_value, new_value) = (field_ref(old, attr), field_ref(new, attr));
+        if old_value != new_value {
+            changes.push(MetaDataChange {
+                key: name.to_string(),
+                old: Some(old_value.to_string()),
+                new: Some(new_value.to_string()),
+            });
+        }
+    }
+
+    for (key, old_value) in old.md.iter() {
+        let new_value = new.md.get(key);
+        if new_value != Some(old_value) {
+            changes.push(MetaDataChange {
+                key: format!("md:{}", key),
+                old: Some(old_value.clone()),
+                new: new_value.cloned(),
+            });
+        }
+    }
+    for (key, new_value) in new.md.iter() {
+        if !old.md.contains_key(key) {
+            changes.push(MetaDataChange {
+                key: format!("md:{}", key),
+                old: None,
+                new: Some(new_value.clone()),
+            });
+        }
+    }
+
+    if old.ds_description != new.ds_description {
+        changes.push(MetaDataChange {
+            key: "dsDescription".to_string(),
+            old: Some(old.ds_description.join(", ")),
+            new: Some(new.ds_description.join(", ")),
+        });
+    }
+    changes
+}
+
+/// The attribute a key refers to, `None` if it addresses an `md` entry.
+fn field(key: &str) -> Option<&'static str> {
+    FIELDS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, attr)| *attr)
+}
+
+/// `mdDescription` lists the `md` keys separated by `", "`, so keys must not contain it.
+fn check_md_key(key: &str) -> Result<(), Box<dyn Error>> {
+    if key.contains(", ") {
+        return Err(format!("md key `{}` must not contain \", \"", key).into());
+    }
+    Ok(())
+}
+
+fn md_key(key: &str) -> &str {
+    key.strip_prefix("md:").unwrap_or(key)
+}
+
+fn field_mut<'a>(meta_data: &'a mut DotthzMetaData, attr: &str) -> &'a mut String {
+    match attr {
+        "description" => &mut meta_data.description,
+        "date" => &mut meta_data.date,
+        "time" => &mut meta_data.time,
+        "mode" => &mut meta_data.mode,
+        "instrument" => &mut meta_data.instrument,
+        "thzVer" => &mut meta_data.version,
+        "user" => &mut meta_data.user,
+        "email" => &mut meta_data.email,
+        "orcid" => &mut meta_data.orcid,
+        "institution" => &mut meta_data.institution,
+        _ => unreachable!("unknown meta-data field `{}`", attr),
+    }
+}
+
+fn field_ref<'a>(meta_data: &'a DotthzMetaData, attr: &str) -> &'a str {
+    match attr {
+        "description" => &meta_data.description,
+        "date" => &meta_data.date,
+        "time" => &meta_data.time,
+        "mode" => &meta_data.mode,
+        "instrument" => &meta_data.instrument,
+        "thzVer" => &meta_data.version,
+        "user" => &meta_data.user,
+        "email" => &meta_data.email,
+        "orcid" => &meta_data.orcid,
+        "institution" => &meta_data.institution,
+        _ => unreachable!("unknown meta-data field `{}`", attr),
+    }
+}
@@ -0,0 +1,114 @@
+use crate::backend::{Backend, Hdf5Backend};
+use crate::dotthz::ds_index;
+use crate::{DatasetOptions, DotthzMetaData};
+use hdf5::{Dataset, Group, H5Type};
+use ndarray::ArrayView;
+use std::error::Error;
+use std::fmt::Debug;
+use std::ops::Deref;
+
+/// A measurement group of a [`DotthzFile`](crate::DotthzFile), returned by
+/// [`DotthzFile::add_group`](crate::DotthzFile::add_group) and
+/// [`DotthzFile::get_group`](crate::DotthzFile::get_group).
+///
+/// Reads and writes the meta-data and datasets of the group without going through its name.
+/// Dereferences to the underlying [`hdf5::Group`] for everything else.
+#[derive(Debug, Clone)]
+pub struct DotthzGroup {
+    group: Group,
+}
+
+impl From<Group> for DotthzGroup {
+    fn from(group: Group) -> Self {
+        DotthzGroup { group }
+    }
+}
+
+impl Deref for DotthzGroup {
+    type Target = Group;
+
+    fn deref(&self) -> &Group {
+        &self.group
+    }
+}
+
+impl DotthzGroup {
+    /// The underlying HDF5 group.
+    pub fn into_inner(self) -> Group {
+        self.group
+    }
+
+    /// Read the meta-data of the group.
+    pub fn meta_data(&self) -> hdf5::Result<DotthzMetaData> {
+        self.backend()?
+            .get_meta_data(&self.group.name())
+            .map_err(|e| hdf5::Error::from(e.to_string()))
+    }
+
+    /// Replace the meta-data of the group, removing `mdN` attributes that are no longer used.
+    pub fn set_meta_data(&self, meta_data: &DotthzMetaData) -> Result<(), Box<dyn Error>> {
+        self.backend()?
+            .update_meta_data(&self.group.name(), meta_data)
+    }
+
+    /// The datasets of the group, `dsN` datasets ordered by `N`.
+    pub fn datasets(&self) -> hdf5::Result<Vec<Dataset>> {
+        let mut datasets = self.group.datasets()?;
+        datasets.sort_by_key(|dataset| {
+            let name = dataset.name();
+            let name = name.rsplit('/').next().unwrap_or(&name).to_string();
+            (ds_index(&name).unwrap_or(usize::MAX), name)
+        });
+        Ok(datasets)
+    }
+
+    /// Get a dataset of the group by name.
+    pub fn dataset(&self, dataset_name: &str) -> hdf5::Result<Dataset> {
+        self.group.dataset(dataset_name)
+    }
+
+    /// Add a dataset to the group.
+    pub fn add_dataset<T, D>(
+        &self,
+        dataset_name: &str,
+        dataset: ArrayView<'_, T, D>,
+    ) -> Result<Dataset, Box<dyn Error>>
+    where
+        T: H5Type + Debug,
+        D: ndarray::Dimension,
+    {
+        self.add_dataset_with(dataset_name, dataset, &DatasetOptions::default())
+    }
+
+    /// Add a dataset to the group with the given chunking and compression options.
+    ///
+    /// If a filter is set but no chunk shape, the chunk shape is chosen with
+    /// [`DatasetOptions::auto_chunk`].
+    pub fn add_dataset_with<T, D>(
+        &self,
+        dataset_name: &str,
+        dataset: ArrayView<'_, T, D>,
+        options: &DatasetOptions,
+    ) -> Result<Dataset, Box<dyn Error>>
+    where
+        T: H5Type + Debug,
+        D: ndarray::Dimension,
+    {
+        options.validate()?;
+        let mut builder = self.group.new_dataset::<T>().shape(dataset.shape());
+        if options.is_chunked() {
+            let chunk = options.chunk.clone().unwrap_or_else(|| {
+                DatasetOptions::auto_chunk(dataset.shape(), std::mem::size_of::<T>())
+            });
+            builder = builder.chunk(chunk).set_filters(&options.filters());
+        }
+        let ds = builder.create(dataset_name)?;
+        ds.write(dataset)?;
+        Ok(ds)
+    }
+
+    /// The backend of the file holding the group.
+    fn backend(&self) -> hdf5::Result<Hdf5Backend> {
+        Ok(self.group.file()?.into())
+    }
+}
@@ -3,8 +3,70 @@
 #![deny(missing_docs)]
 #![deny(warnings)]
 
+mod append;
+mod array;
+#[cfg(feature = "arrow")]
+mod arrow;
+mod attributes;
+mod backend;
+mod complex;
+mod csv;
+#[cfg(feature = "serde")]
+mod document;
 mod dotthz;
+mod edit;
+mod group;
+#[cfg(feature = "mat")]
+mod mat;
+mod netcdf;
+#[cfg(feature = "npy")]
+mod npy;
+mod options;
+mod paths;
+mod scales;
+#[cfg(feature = "sidecar")]
+mod sidecar;
+mod slice;
+mod swmr;
+mod touchstone;
+mod traverse;
+mod validate;
+pub use append::Appender;
+pub use array::TypedArray;
+#[cfg(feature = "arrow")]
+pub use arrow::{export_parquet, files_to_record_batch, to_record_batch, write_parquet};
+pub use attributes::{si_factor, DatasetAttributes};
+pub use backend::{AttributeValue, Backend, Hdf5Backend, MemoryBackend};
+pub use complex::{ComplexFloat, ComplexLayout};
+pub use csv::{
+    export_csv, export_csv_to_path, import_csv, import_csv_table, parse_csv, read_csv,
+    CsvExportOptions, CsvImportOptions, CsvOptions, CsvTable,
+};
+#[cfg(feature = "serde")]
+pub use document::{DatasetDocument, DotthzDocument, GroupDocument};
 pub use dotthz::{DotthzFile, DotthzMetaData};
+pub use edit::{diff_meta_data, MetaDataChange, MetaDataEdit};
+pub use group::DotthzGroup;
+#[cfg(feature = "hdf5-blosc")]
+pub use hdf5::filters::{Blosc, BloscShuffle};
+#[cfg(feature = "mat")]
+pub use mat::{export_mat, import_mat};
+pub use netcdf::export_netcdf;
+#[cfg(feature = "npy")]
+pub use npy::{export_npy, export_npz, import_npy, import_npz, meta_data_sidecar_path};
+pub use options::DatasetOptions;
+pub use paths::collect_thz_files;
+pub use scales::is_dimension_scale;
+#[cfg(feature = "sidecar")]
+pub use sidecar::{load_meta_data, save_meta_data};
+pub use swmr::{TraceWatcher, Watch};
+pub use touchstone::{
+    add_spectrum, export_touchstone, format_touchstone, import_touchstone, parse_touchstone,
+    read_spectrum, NoiseParameters, Touchstone, NOISE_DESCRIPTION, PARAMETER_KEY,
+    REFERENCE_IMPEDANCE_KEY,
+};
+pub use traverse::{DatasetsIter, DotthzVisitor, GroupsIter};
+pub use validate::{validate, validate_file, Diagnostic, Severity, ValidationReport};
 
 #[cfg(test)]
 mod tests {
@@ -159,4 +221,1966 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_validate() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_file = NamedTempFile::new()?;
+        let path: PathBuf = temp_file.path().to_path_buf();
+
+        let meta_data = DotthzMetaData {
+            user: "Test User".to_string(),
+            email: "test@example.com".to_string(),
+            orcid: "0000-0001-2345-6789".to_string(),
+            institution: "Test Institute".to_string(),
+            description: "Test description".to_string(),
+            md: [("Thickness (mm)".to_string(), "0.52".to_string())]
+                .into_iter()
+                .collect(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            version: "1.0".to_string(),
+            mode: "Test mode".to_string(),
+            instrument: "Test instrument".to_string(),
+            time: "12:34:56".to_string(),
+            date: "2024-11-08".to_string(),
+        };
+
+        let mut file = DotthzFile::create(&path)?;
+        file.add_group("Measurement", &meta_data)?;
+        let data: Array2<f32> = array![[1.0, 2.0], [3.0, 4.0]];
+        file.add_dataset("Measurement", "ds1", data.view())?;
+
+        // only one of the two described datasets exists
+        let report = validate_file(&path);
+        assert!(report.has_errors());
+        assert!(report
+            .diagnostics
+            .iter()
+            .any(|d| d.message.contains("dsDescription")));
+
+        file.add_dataset("Measurement", "ds2", data.view())?;
+        let report = validate_file(&path);
+        assert!(!report.has_errors(), "{:?}", report.diagnostics);
+
+        // dsDescription entries are positional, not prefixed with the dataset name
+        let prefixed = DotthzMetaData {
+            ds_description: vec!["ds1:Reference".to_string(), "ds2:Sample".to_string()],
+            ..meta_data.clone()
+        };
+        file.set_meta_data("Measurement", &prefixed)?;
+        let report = validate_file(&path);
+        assert!(report
+            .diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error && d.message.contains("ds1:Reference")));
+
+        // a missing file is reported, not returned
+        let report = validate_file("test_files/does_not_exist.thz");
+        assert!(report.has_errors());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_meta_data() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_file = NamedTempFile::new()?;
+        let path: PathBuf = temp_file.path().to_path_buf();
+
+        let meta_data = DotthzMetaData {
+            description: "Tset description".to_string(),
+            md: [("Thickness (mm)".to_string(), "0.52".to_string())]
+                .into_iter()
+                .collect(),
+            version: "1.0".to_string(),
+            ..Default::default()
+        };
+
+        let mut file = DotthzFile::create(&path)?;
+        file.add_group("Measurement", &meta_data)?;
+
+        let mut edited = meta_data.clone();
+        for edit in [
+            MetaDataEdit::Set {
+                key: "description".to_string(),
+                value: "Test description".to_string(),
+            },
+            MetaDataEdit::Set {
+                key: "Humidity (%)".to_string(),
+                value: "45".to_string(),
+            },
+            MetaDataEdit::Rename {
+                from: "Thickness (mm)".to_string(),
+                to: "Thickness (um)".to_string(),
+            },
+        ] {
+            edit.apply(&mut edited)?;
+        }
+        assert_eq!(diff_meta_data(&meta_data, &edited).len(), 4);
+        assert_eq!(
+            edited.md.keys().collect::<Vec<_>>(),
+            ["Thickness (um)", "Humidity (%)"]
+        );
+
+        file.set_meta_data("Measurement", &edited)?;
+        assert_eq!(file.get_meta_data("Measurement")?, edited);
+
+        // removing an entry drops its attribute as well
+        MetaDataEdit::Unset {
+            key: "Humidity (%)".to_string(),
+        }
+        .apply(&mut edited)?;
+        file.set_meta_data("Measurement", &edited)?;
+        assert_eq!(file.get_meta_data("Measurement")?, edited);
+        assert!(file.get_group("Measurement")?.attr("md2").is_err());
+
+        // the deprecated `update_meta_data` replaces it the same way
+        let mut extended = edited.clone();
+        extended
+            .md
+            .insert("Humidity (%)".to_string(), "45".to_string());
+        #[allow(deprecated)]
+        file.update_meta_data("Measurement", &extended)?;
+        assert_eq!(file.get_meta_data("Measurement")?, extended);
+        file.set_meta_data("Measurement", &edited)?;
+        assert_eq!(file.get_meta_data("Measurement")?, edited);
+        assert!(file.get_group("Measurement")?.attr("md2").is_err());
+
+        // unknown md entries are rejected
+        assert!(MetaDataEdit::Unset {
+            key: "Pressure (bar)".to_string(),
+        }
+        .apply(&mut edited)
+        .is_err());
+
+        // renaming an entry to itself changes nothing
+        let unchanged = edited.clone();
+        MetaDataEdit::Rename {
+            from: "Thickness (um)".to_string(),
+            to: "Thickness (um)".to_string(),
+        }
+        .apply(&mut edited)?;
+        assert_eq!(edited, unchanged);
+
+        // keys containing the mdDescription separator are rejected
+        assert!(MetaDataEdit::Set {
+            key: "Temperature, Humidity".to_string(),
+            value: "300".to_string(),
+        }
+        .apply(&mut edited)
+        .is_err());
+        assert_eq!(edited, unchanged);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_csv() -> Result<(), Box<dyn std::error::Error>> {
+        // semicolon delimited with decimal commas and a comment preamble
+        let text = "# exported by a lock-in\ntime (ps);Reference;Sample\n0,0;1,5;0,5\n0,1;2,5;1,0\n0,2;-1,0;-0,5\n";
+        let table = parse_csv(text, &CsvOptions::default())?;
+        assert_eq!(table.headers, ["time (ps)", "Reference", "Sample"]);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.columns[1], [1.5, 2.5, -1.0]);
+
+        // the same data, tab separated without header
+        let text = "0.0\t1.5\t0.5\n0.1\t2.5\t1.0\n0.2\t-1.0\t-0.5\n";
+        let headerless = parse_csv(text, &CsvOptions::default())?;
+        assert_eq!(headerless.columns, table.columns);
+        assert_eq!(headerless.headers[1], "Column 2");
+
+        let temp_file = NamedTempFile::new()?;
+        let path: PathBuf = temp_file.path().to_path_buf();
+        let mut file = DotthzFile::create(&path)?;
+        let meta_data = DotthzMetaData {
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        import_csv_table(
+            &mut file,
+            "Measurement",
+            &table,
+            &meta_data,
+            &CsvImportOptions::default(),
+        )?;
+
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            ["Reference", "Sample"]
+        );
+        let sample = file.get_dataset("Measurement", "ds2")?.read_2d::<f64>()?;
+        assert_eq!(sample, array![[0.0, 0.1, 0.2], [0.5, 1.0, -0.5]]);
+        assert!(!validate(&file)?
+            .iter()
+            .any(|d| d.severity == Severity::Error));
+
+        // a failing import leaves no group behind
+        let described = DotthzMetaData {
+            ds_description: vec!["Reference".to_string()],
+            ..meta_data.clone()
+        };
+        assert!(import_csv_table(
+            &mut file,
+            "Mismatch",
+            &table,
+            &described,
+            &CsvImportOptions::default()
+        )
+        .is_err());
+        assert!(file.get_group("Mismatch").is_err());
+
+        // quoted fields may hold the delimiter, quotes and line breaks
+        let text = "time,\"Sample, 300K (nA)\",\"5\"\" wafer\nback\"\n0.0,1.5,0.5\n";
+        let quoted = parse_csv(text, &CsvOptions::default())?;
+        assert_eq!(
+            quoted.headers,
+            ["time", "Sample, 300K (nA)", "5\" wafer\nback"]
+        );
+        assert_eq!(quoted.columns, [[0.0], [1.5], [0.5]]);
+
+        // exported headers containing the delimiter are read back
+        let described = DotthzMetaData {
+            ds_description: vec!["Sample,300K".to_string(), "5\" wafer".to_string()],
+            ..meta_data.clone()
+        };
+        import_csv_table(
+            &mut file,
+            "Quoted",
+            &table,
+            &described,
+            &CsvImportOptions::default(),
+        )?;
+        let mut output = Vec::new();
+        export_csv(&file, "Quoted", &mut output, &CsvExportOptions::default())?;
+        let exported = parse_csv(std::str::from_utf8(&output)?, &CsvOptions::default())?;
+        assert_eq!(
+            exported.headers,
+            [
+                "Sample,300K time",
+                "Sample,300K signal",
+                "5\" wafer time",
+                "5\" wafer signal"
+            ]
+        );
+        assert_eq!(exported.columns[1], table.columns[1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_file = NamedTempFile::new()?;
+        let path: PathBuf = temp_file.path().to_path_buf();
+        let mut file = DotthzFile::create(&path)?;
+        let meta_data = DotthzMetaData {
+            instrument: "Test instrument".to_string(),
+            md: [("Thickness (mm)".to_string(), "0.52".to_string())]
+                .into_iter()
+                .collect(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        file.add_group("Measurement", &meta_data)?;
+        let reference: Array2<f64> = array![[0.0, 0.1, 0.2], [1.5, 2.5, -1.0]];
+        let sample: Array2<f64> = array![[0.0, 0.1], [0.5, 1.0]];
+        file.add_dataset("Measurement", "ds1", reference.view())?;
+        file.add_dataset("Measurement", "ds2", sample.view())?;
+
+        let mut output = Vec::new();
+        let options = CsvExportOptions {
+            meta_data_preamble: true,
+            ..Default::default()
+        };
+        export_csv(&file, "Measurement", &mut output, &options)?;
+        let text = String::from_utf8(output)?;
+        assert!(text.starts_with("# instrument: Test instrument\n"));
+        assert!(text.contains("# Thickness (mm): 0.52\n"));
+
+        // the shorter dataset is padded with empty fields
+        let mut lines = text.lines().skip_while(|l| l.starts_with('#'));
+        assert_eq!(
+            lines.next(),
+            Some("Reference time,Reference signal,Sample time,Sample signal")
+        );
+        assert_eq!(lines.next(), Some("0,1.5,0,0.5"));
+        assert_eq!(lines.nth(1), Some("0.2,-1,,"));
+
+        // only the selected datasets are exported
+        let mut output = Vec::new();
+        let options = CsvExportOptions {
+            datasets: vec!["ds2".to_string()],
+            ..Default::default()
+        };
+        export_csv(&file, "Measurement", &mut output, &options)?;
+        let table = parse_csv(std::str::from_utf8(&output)?, &CsvOptions::default())?;
+        assert_eq!(table.headers, ["Sample time", "Sample signal"]);
+        assert_eq!(table.columns[1], [0.5, 1.0]);
+
+        // a failed export does not leave a file behind
+        let dir = tempfile::tempdir()?;
+        let csv_path = dir.path().join("missing.csv");
+        assert!(export_csv_to_path(&file, "Missing", &csv_path, &options).is_err());
+        assert!(!csv_path.exists());
+        export_csv_to_path(&file, "Measurement", &csv_path, &options)?;
+        assert_eq!(std::fs::read(&csv_path)?, output);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_thz_files() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let nested = dir.path().join("nested");
+        std::fs::create_dir(&nested)?;
+        for path in [
+            dir.path().join("b.thz"),
+            dir.path().join("a.THZ"),
+            dir.path().join("notes.txt"),
+            nested.join("c.thz"),
+        ] {
+            std::fs::write(path, [])?;
+        }
+
+        let files = collect_thz_files(&[dir.path()], false)?;
+        assert_eq!(files, [dir.path().join("a.THZ"), dir.path().join("b.thz")]);
+
+        // recursive search, explicit files are kept and duplicates removed
+        let notes = dir.path().join("notes.txt");
+        let files = collect_thz_files(&[dir.path(), &notes, &nested], true)?;
+        assert_eq!(
+            files,
+            [
+                dir.path().join("a.THZ"),
+                dir.path().join("b.thz"),
+                nested.join("c.thz"),
+                notes,
+            ]
+        );
+        assert!(collect_thz_files(&[dir.path().join("missing.thz")], false).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "npy")]
+    fn test_npz_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("original.thz");
+        let mut file = DotthzFile::create(&path)?;
+        let meta_data = DotthzMetaData {
+            user: "Test User".to_string(),
+            md: [("Thickness (mm)".to_string(), "0.52".to_string())]
+                .into_iter()
+                .collect(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        file.add_group("Measurement", &meta_data)?;
+        let reference: Array2<f32> = array![[0.0, 0.1, 0.2], [1.5, 2.5, -1.0]];
+        let sample: Array2<f64> = array![[0.0, 0.1], [0.5, 1.0]];
+        file.add_dataset("Measurement", "ds1", reference.view())?;
+        file.add_dataset("Measurement", "ds2", sample.view())?;
+
+        let npz_path = dir.path().join("Measurement.npz");
+        export_npz(&file, "Measurement", &npz_path)?;
+        assert!(meta_data_sidecar_path(&npz_path).exists());
+
+        let copy_path = dir.path().join("copy.thz");
+        let mut copy = DotthzFile::create(&copy_path)?;
+        import_npz(&mut copy, "Measurement", &npz_path)?;
+        assert_eq!(copy.get_meta_data("Measurement")?, meta_data);
+
+        // element types survive the round trip
+        let ds1 = copy.get_dataset("Measurement", "ds1")?;
+        assert!(ds1.dtype()?.is::<f32>());
+        assert_eq!(ds1.read_2d::<f32>()?, reference);
+        assert_eq!(
+            copy.get_dataset("Measurement", "ds2")?.read_2d::<f64>()?,
+            sample
+        );
+
+        let npy_path = dir.path().join("ds2.npy");
+        export_npy(&file, "Measurement", "ds2", &npy_path)?;
+        import_npy(&mut copy, "Measurement", "ds3", &npy_path)?;
+        assert_eq!(
+            copy.get_dataset("Measurement", "ds3")?.read_2d::<f64>()?,
+            sample
+        );
+
+        // an entry of an unsupported type fails the import without leaving the group behind
+        let bytes_path = dir.path().join("bytes.npz");
+        let mut npz = ndarray_npy::NpzWriter::new(std::fs::File::create(&bytes_path)?);
+        npz.add_array("ds1", &sample)?;
+        npz.add_array("ds2", &array![1u8, 2, 3])?;
+        npz.finish()?;
+        assert!(import_npz(&mut copy, "Bytes", &bytes_path).is_err());
+        assert!(copy.get_group("Bytes").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "mat")]
+    fn test_mat_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("original.thz");
+        let mut file = DotthzFile::create(&path)?;
+        let meta_data = DotthzMetaData {
+            user: "Test User".to_string(),
+            instrument: "Test instrument".to_string(),
+            md: [
+                ("Thickness (mm)".to_string(), "0.52".to_string()),
+                ("Humidity (%)".to_string(), "45".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        file.add_group("Sample 1", &meta_data)?;
+        let reference: Array2<f32> = array![[0.0, 0.1, 0.2], [1.5, 2.5, -1.0]];
+        let sample: Array2<f64> = array![[0.0, 0.1, 0.2], [0.5, 1.0, 0.25]];
+        file.add_dataset("Sample 1", "ds1", reference.view())?;
+        file.add_dataset("Sample 1", "ds2", sample.view())?;
+
+        let mat_path = dir.path().join("original.mat");
+        export_mat(&file, &mat_path)?;
+
+        let copy_path = dir.path().join("copy.thz");
+        let mut copy = DotthzFile::create(&copy_path)?;
+        import_mat(&mut copy, &mat_path)?;
+
+        // the group name is restored from `meta.group`, md keys from `meta.md_description`
+        assert_eq!(copy.get_meta_data("Sample 1")?, meta_data);
+        let ds1 = copy.get_dataset("Sample 1", "ds1")?;
+        assert!(ds1.dtype()?.is::<f32>());
+        assert_eq!(ds1.read_2d::<f32>()?, reference);
+        assert_eq!(
+            copy.get_dataset("Sample 1", "ds2")?.read_2d::<f64>()?,
+            sample
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "mat")]
+    fn test_mat_nested_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("campaign.thz"))?;
+        let campaign = DotthzMetaData {
+            user: "John Doe".to_string(),
+            ..Default::default()
+        };
+        file.add_group("Campaign", &campaign)?;
+        file.add_group("Campaign/300K", &DotthzMetaData::default())?;
+        let data: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        file.add_described_dataset("Campaign/300K", "Sample", data.view())?;
+
+        // nested measurement groups are exported with their inherited meta-data
+        let mat_path = dir.path().join("campaign.mat");
+        export_mat(&file, &mat_path)?;
+        let mut copy = DotthzFile::create(&dir.path().join("copy.thz"))?;
+        import_mat(&mut copy, &mat_path)?;
+        assert_eq!(
+            copy.get_meta_data("Campaign/300K")?,
+            file.get_inherited_meta_data("Campaign/300K")?
+        );
+        assert_eq!(copy.get_meta_data("Campaign/300K")?.user, "John Doe");
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "arrow")]
+    fn test_record_batch() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("traces.thz");
+        let mut file = DotthzFile::create(&path)?;
+        let meta_data = DotthzMetaData {
+            user: "Test User".to_string(),
+            md: [("Thickness (mm)".to_string(), "0.52".to_string())]
+                .into_iter()
+                .collect(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        file.add_group("Measurement", &meta_data)?;
+        let reference: Array2<f64> = array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0], [1.1, 2.1, 3.1]];
+        let sample: Array2<f64> = array![[0.0, 0.1, 0.2], [0.5, 1.0, 1.5]];
+        file.add_dataset("Measurement", "ds1", reference.view())?;
+        file.add_dataset("Measurement", "ds2", sample.view())?;
+
+        // two traces of ds1 and one of ds2
+        let batch = to_record_batch(&file, "traces.thz")?;
+        assert_eq!(batch.num_rows(), 3);
+        let schema = batch.schema();
+        for column in [
+            "file",
+            "group",
+            "dataset",
+            "trace",
+            "user",
+            "ds_description",
+        ] {
+            assert!(schema.column_with_name(column).is_some(), "{}", column);
+        }
+        assert!(schema.column_with_name("Thickness (mm)").is_some());
+        assert!(schema.column_with_name("time_axis").is_some());
+        assert!(schema.column_with_name("signal").is_some());
+
+        let parquet_path = dir.path().join("traces.parquet");
+        drop(file);
+        export_parquet(&[dir.path()], &parquet_path)?;
+        assert!(parquet_path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_document_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("original.thz"))?;
+        let meta_data = DotthzMetaData {
+            user: "Test User".to_string(),
+            md: [("Thickness (mm)".to_string(), "0.52".to_string())]
+                .into_iter()
+                .collect(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        file.add_group("Measurement", &meta_data)?;
+        let reference: Array2<f32> = array![[0.0, 0.1, 0.2], [1.5, 2.5, -1.0]];
+        let sample: Array2<i64> = array![[0, 1], [5, 10]];
+        file.add_dataset("Measurement", "ds1", reference.view())?;
+        file.add_dataset("Measurement", "ds2", sample.view())?;
+
+        let document = DotthzDocument::from_file(&file)?;
+        assert_eq!(document.groups[0].name, "Measurement");
+        assert_eq!(document.groups[0].datasets[1].data.shape(), [2, 2]);
+
+        let json = serde_json::to_string(&document)?;
+        let parsed: DotthzDocument = serde_json::from_str(&json)?;
+        assert_eq!(parsed, document);
+
+        let copy = parsed.create_file(&dir.path().join("copy.thz"))?;
+        assert_eq!(copy.get_meta_data("Measurement")?, meta_data);
+        let ds1 = copy.get_dataset("Measurement", "ds1")?;
+        assert!(ds1.dtype()?.is::<f32>());
+        assert_eq!(ds1.read_2d::<f32>()?, reference);
+        assert_eq!(
+            copy.get_dataset("Measurement", "ds2")?.read_2d::<i64>()?,
+            sample
+        );
+        assert_eq!(DotthzDocument::from_file(&copy)?, document);
+
+        // nested groups follow their parent with the full path
+        file.add_group("Campaign/300K", &meta_data)?;
+        file.add_dataset("Campaign/300K", "ds1", reference.view())?;
+        let document = DotthzDocument::from_file(&file)?;
+        let names = document
+            .groups
+            .iter()
+            .map(|group| group.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, ["Campaign", "Campaign/300K", "Measurement"]);
+        let copy = document.create_file(&dir.path().join("nested.thz"))?;
+        assert_eq!(copy.get_meta_data("Campaign/300K")?, meta_data);
+        assert_eq!(DotthzDocument::from_file(&copy)?, document);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_spectra() -> Result<(), Box<dyn std::error::Error>> {
+        use num_complex::Complex64;
+
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("spectra.thz"))?;
+        file.add_group("Spectra", &DotthzMetaData::default())?;
+        let frequency = [1.0e9, 2.0e9, 3.0e9];
+        let values = [
+            Complex64::new(0.5, -0.25),
+            Complex64::new(0.4, 0.1),
+            Complex64::new(-0.3, 0.2),
+        ];
+        add_spectrum(&mut file, "Spectra", "S21", &frequency, &values)?;
+
+        #[cfg(feature = "npy")]
+        {
+            let npz_path = dir.path().join("spectra.npz");
+            export_npz(&file, "Spectra", &npz_path)?;
+            let mut copy = DotthzFile::create(&dir.path().join("npz.thz"))?;
+            import_npz(&mut copy, "Spectra", &npz_path)?;
+            let spectrum = copy.read_complex::<f64>("Spectra", "S21")?;
+            assert_eq!(spectrum.iter().copied().collect::<Vec<Complex64>>(), values);
+        }
+
+        #[cfg(feature = "mat")]
+        {
+            let mat_path = dir.path().join("spectra.mat");
+            export_mat(&file, &mat_path)?;
+            let mut copy = DotthzFile::create(&dir.path().join("mat.thz"))?;
+            import_mat(&mut copy, &mat_path)?;
+            let spectrum = copy.read_complex::<f64>("Spectra", "S21")?;
+            assert_eq!(spectrum.iter().copied().collect::<Vec<Complex64>>(), values);
+        }
+
+        // documents keep the units of the axis and the attached axis
+        #[cfg(feature = "serde")]
+        {
+            let document = DotthzDocument::from_file(&file)?;
+            let datasets = &document.groups[0].datasets;
+            let axis = datasets.iter().find(|d| d.name == "frequency").unwrap();
+            assert!(axis.is_axis);
+            assert_eq!(axis.attributes.units, ["Hz"]);
+            let spectrum = datasets.iter().find(|d| d.name == "S21").unwrap();
+            assert_eq!(spectrum.axes[0].as_deref(), Some("frequency"));
+
+            let json = serde_json::to_string(&document)?;
+            let copy = serde_json::from_str::<DotthzDocument>(&json)?
+                .create_file(&dir.path().join("document.thz"))?;
+            assert_eq!(
+                read_spectrum(&copy, "Spectra", "S21")?,
+                (frequency.to_vec(), values.to_vec())
+            );
+            assert_eq!(DotthzDocument::from_file(&copy)?, document);
+        }
+
+        #[cfg(feature = "arrow")]
+        {
+            let batch = to_record_batch(&file, "spectra.thz")?;
+            assert!(batch.num_rows() >= 1);
+            assert!(batch.schema().column_with_name("signal_imag").is_some());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_touchstone_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("extender.s2p");
+        std::fs::write(
+            &path,
+            "! two-port measurement\n\
+             # GHz S MA R 50\n\
+             500 0.5 90 0.9 -45 0.9 -45 0.4 180\n\
+             510 0.6 0 0.8 -50 0.8 -50 0.3 170\n\
+             ! noise parameters\n\
+             500 1.5 0.3 45 0.2\n\
+             505 1.6 0.35 50 0.25\n",
+        )?;
+
+        let mut file = DotthzFile::create(&dir.path().join("spectra.thz"))?;
+        import_touchstone(&mut file, "Extender", &path, &DotthzMetaData::default())?;
+        let meta_data = file.get_meta_data("Extender")?;
+        assert_eq!(
+            meta_data.ds_description,
+            vec!["S11", "S21", "S12", "S22", NOISE_DESCRIPTION]
+        );
+        assert_eq!(
+            meta_data
+                .md
+                .get(REFERENCE_IMPEDANCE_KEY)
+                .map(String::as_str),
+            Some("50")
+        );
+        assert_eq!(
+            meta_data.md.get(PARAMETER_KEY).map(String::as_str),
+            Some("S")
+        );
+
+        // spectra are complex datasets sharing one frequency axis
+        let (frequency, s11) = read_spectrum(&file, "Extender", "ds1")?;
+        assert_eq!(frequency, vec![500e9, 510e9]);
+        assert!((s11[0].re - 0.0).abs() < 1e-12 && (s11[0].im - 0.5).abs() < 1e-12);
+        assert!((s11[1].re - 0.6).abs() < 1e-12 && s11[1].im.abs() < 1e-12);
+        assert_eq!(file.read_complex::<f64>("Extender", "ds4")?.shape(), [2]);
+        assert_eq!(
+            file.get_axis("Extender", "ds4", 0)?.map(|a| a.name()),
+            Some("/Extender/frequency".to_string())
+        );
+        let noise = file.get_dataset("Extender", "ds5")?.read_2d::<f64>()?;
+        assert_eq!(noise.row(0).to_vec(), vec![500e9, 505e9]);
+        assert_eq!(noise.row(1).to_vec(), vec![1.5, 1.6]);
+
+        let exported = dir.path().join("exported.s2p");
+        export_touchstone(&file, "Extender", &exported)?;
+        let original = parse_touchstone(&std::fs::read_to_string(&path)?, 2)?;
+        let copy = parse_touchstone(&std::fs::read_to_string(&exported)?, 2)?;
+        assert_eq!(copy.frequency, original.frequency);
+        for (a, b) in copy
+            .values
+            .iter()
+            .flatten()
+            .zip(original.values.iter().flatten())
+        {
+            assert!((a - b).norm() < 1e-12);
+        }
+        assert_eq!(copy.noise.len(), 2);
+        for (a, b) in copy.noise.iter().zip(original.noise.iter()) {
+            assert_eq!(a.frequency, b.frequency);
+            assert!((a.optimal_reflection - b.optimal_reflection).norm() < 1e-12);
+        }
+
+        // the parameter type is kept
+        let path = dir.path().join("impedance.s1p");
+        std::fs::write(&path, "# MHz Z RI R 75\n100 50 -5\n200 48 -10\n")?;
+        import_touchstone(&mut file, "Impedance", &path, &DotthzMetaData::default())?;
+        assert_eq!(file.get_meta_data("Impedance")?.ds_description, vec!["Z11"]);
+        let exported = dir.path().join("exported.s1p");
+        export_touchstone(&file, "Impedance", &exported)?;
+        let copy = parse_touchstone(&std::fs::read_to_string(&exported)?, 1)?;
+        assert_eq!(copy.parameter, 'Z');
+        assert_eq!(copy.reference_impedance, 75.0);
+        assert_eq!(copy.frequency, vec![100e6, 200e6]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_touchstone_multiport() -> Result<(), Box<dyn std::error::Error>> {
+        use num_complex::Complex64;
+
+        // more than two ports are written row-wise, at most four values per line
+        let touchstone = Touchstone {
+            ports: 3,
+            parameter: 'S',
+            reference_impedance: 50.0,
+            frequency: vec![1e9, 2e9],
+            values: (0..9)
+                .map(|k| vec![Complex64::new(k as f64, 0.5); 2])
+                .collect(),
+            noise: Vec::new(),
+        };
+        let text = format_touchstone(&touchstone);
+        assert_eq!(text.lines().count(), 1 + 2 * 3);
+        assert!(text
+            .lines()
+            .skip(1)
+            .all(|line| line.split_whitespace().count() <= 1 + 2 * 4));
+        assert_eq!(parse_touchstone(&text, 3)?, touchstone);
+        assert_eq!(touchstone.names()[1], "S12");
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("coupler.s3p");
+        std::fs::write(&path, &text)?;
+        let mut file = DotthzFile::create(&dir.path().join("coupler.thz"))?;
+        import_touchstone(&mut file, "Coupler", &path, &DotthzMetaData::default())?;
+        let exported = dir.path().join("exported.s3p");
+        export_touchstone(&file, "Coupler", &exported)?;
+        assert_eq!(std::fs::read_to_string(&exported)?, text);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_complex_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        use num_complex::Complex;
+
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("complex.thz"))?;
+        file.add_group("Spectrum", &DotthzMetaData::default())?;
+        let spectrum: Array2<Complex<f64>> = array![
+            [Complex::new(1.0, 0.5), Complex::new(-0.25, 2.0)],
+            [Complex::new(0.0, -1.0), Complex::new(3.0, 0.0)]
+        ];
+        let single = spectrum.mapv(|c| Complex::new(c.re as f32, c.im as f32));
+
+        file.add_complex_dataset_with_layout(
+            "Spectrum",
+            "ds1",
+            spectrum.view(),
+            ComplexLayout::Interleaved,
+        )?;
+        assert_eq!(file.get_dataset("Spectrum", "ds1")?.shape(), vec![2, 2, 2]);
+        assert_eq!(
+            file.read_complex::<f64>("Spectrum", "ds1")?,
+            spectrum.clone().into_dyn()
+        );
+
+        file.add_complex_dataset("Spectrum", "ds2", single.view())?;
+        assert_eq!(
+            file.read_complex::<f32>("Spectrum", "ds2")?,
+            single.clone().into_dyn()
+        );
+
+        #[cfg(feature = "hdf5-complex")]
+        {
+            file.add_complex_dataset_with_layout(
+                "Spectrum",
+                "ds3",
+                spectrum.view(),
+                ComplexLayout::Native,
+            )?;
+            assert_eq!(file.get_dataset("Spectrum", "ds3")?.shape(), vec![2, 2]);
+            assert_eq!(
+                file.read_complex::<f64>("Spectrum", "ds3")?,
+                spectrum.into_dyn()
+            );
+        }
+        #[cfg(not(feature = "hdf5-complex"))]
+        assert!(file
+            .add_complex_dataset_with_layout(
+                "Spectrum",
+                "ds3",
+                spectrum.view(),
+                ComplexLayout::Native
+            )
+            .is_err());
+
+        // a real dataset without a trailing dimension of 2 is not complex
+        file.add_dataset("Spectrum", "ds4", array![1.0, 2.0, 3.0].view())?;
+        assert!(file.read_complex::<f64>("Spectrum", "ds4").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_dataset_with_options() -> Result<(), Box<dyn std::error::Error>> {
+        use hdf5::filters::Filter;
+
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("scan.thz"))?;
+        file.add_group("Image", &DotthzMetaData::default())?;
+        let scan = Array2::from_shape_fn((64, 4096), |(i, j)| ((i * j) % 17) as f64);
+
+        file.add_dataset_with("Image", "ds1", scan.view(), &DatasetOptions::compressed(6))?;
+        let ds1 = file.get_dataset("Image", "ds1")?;
+        assert_eq!(ds1.filters(), vec![Filter::Shuffle, Filter::Deflate(6)]);
+        // whole traces of 4096 samples in chunks of about 1 MiB
+        assert_eq!(ds1.chunk(), Some(vec![32, 4096]));
+        assert!(ds1.storage_size() < (scan.len() * 8) as u64);
+        assert_eq!(ds1.read_2d::<f64>()?, scan);
+
+        let options = DatasetOptions::default()
+            .with_chunk(&[8, 512])
+            .with_fletcher32();
+        file.add_dataset_with("Image", "ds2", scan.view(), &options)?;
+        let ds2 = file.get_dataset("Image", "ds2")?;
+        assert_eq!(ds2.chunk(), Some(vec![8, 512]));
+        assert_eq!(ds2.filters(), vec![Filter::Fletcher32]);
+        assert_eq!(ds2.read_2d::<f64>()?, scan);
+
+        // without options the dataset stays contiguous
+        file.add_dataset_with("Image", "ds3", scan.view(), &DatasetOptions::default())?;
+        assert!(!file.get_dataset("Image", "ds3")?.is_chunked());
+
+        // gzip levels above 9 are rejected before anything is written
+        assert!(file
+            .add_dataset_with("Image", "ds4", scan.view(), &DatasetOptions::compressed(12))
+            .is_err());
+        assert!(file.get_dataset("Image", "ds4").is_err());
+
+        assert_eq!(DatasetOptions::auto_chunk(&[2, 1000], 8), vec![2, 1000]);
+        assert_eq!(DatasetOptions::auto_chunk(&[0, 10], 4), vec![1, 10]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_traces() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("acquisition.thz");
+        let mut file = DotthzFile::create(&path)?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+
+        let mut appender = file.create_appendable_dataset::<f64>(
+            "Measurement",
+            "ds1",
+            3,
+            &DatasetOptions::default(),
+        )?;
+        appender.set_flush_interval(2);
+        appender.append(&[0.0, 0.1, 0.2])?;
+        appender.append(&[1.0, 2.0, 3.0])?;
+        appender.append_rows(array![[4.0, 5.0, 6.0], [7.0, 8.0, 9.0]].view())?;
+        assert!(appender.append(&[1.0, 2.0]).is_err());
+        assert_eq!(appender.len(), 4);
+        drop(appender);
+        drop(file);
+
+        let file = DotthzFile::open_rw(&path)?;
+        let mut appender = file.open_appender::<f64>("Measurement", "ds1")?;
+        assert_eq!(appender.len(), 4);
+        appender.append(&[-1.0, -2.0, -3.0])?;
+        appender.flush()?;
+        drop(appender);
+
+        let data = file.get_dataset("Measurement", "ds1")?.read_2d::<f64>()?;
+        assert_eq!(
+            data,
+            array![
+                [0.0, 0.1, 0.2],
+                [1.0, 2.0, 3.0],
+                [4.0, 5.0, 6.0],
+                [7.0, 8.0, 9.0],
+                [-1.0, -2.0, -3.0]
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_swmr_watch() -> Result<(), Box<dyn std::error::Error>> {
+        use hdf5::types::VarLenUnicode;
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("live.thz");
+        let mut file = DotthzFile::create_swmr(&path)?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let mut appender = file.create_appendable_dataset::<f64>(
+            "Measurement",
+            "ds1",
+            3,
+            &DatasetOptions::default(),
+        )?;
+        file.start_swmr()?;
+        appender.append(&[0.0, 0.1, 0.2])?;
+        appender.append(&[1.0, 2.0, 3.0])?;
+        appender.flush()?;
+
+        // the writer stays open and keeps appending while the reader follows the dataset
+        let reader = DotthzFile::open_swmr(&path)?;
+        let mut watcher = reader.watch_dataset::<f64>("Measurement", "ds1")?;
+        assert_eq!(watcher.poll()?, array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0]]);
+        assert_eq!(watcher.poll()?.nrows(), 0);
+        appender.append(&[4.0, 5.0, 6.0])?;
+        appender.flush()?;
+        assert_eq!(watcher.poll()?, array![[4.0, 5.0, 6.0]]);
+        assert_eq!(watcher.len(), 3);
+
+        appender.append(&[7.0, 8.0, 9.0])?;
+        appender.flush()?;
+        let batches = watcher
+            .watch(Duration::from_millis(1), Duration::from_millis(10))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(batches, vec![array![[7.0, 8.0, 9.0]]]);
+
+        // watching stops after the first error, here rows that cannot be read as strings
+        let mut watch = reader
+            .watch_dataset::<VarLenUnicode>("Measurement", "ds1")?
+            .watch(Duration::from_millis(1), Duration::from_millis(10));
+        assert!(matches!(watch.next(), Some(Err(_))));
+        assert!(watch.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_slice() -> Result<(), Box<dyn std::error::Error>> {
+        use ndarray::{s, Array3};
+
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("cube.thz"))?;
+        file.add_group("Image", &DotthzMetaData::default())?;
+        let traces: Array2<f64> = array![
+            [0.0, 0.1, 0.2, 0.3, 0.4, 0.5],
+            [1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            [-1.0, -2.0, -3.0, -4.0, -5.0, -6.0]
+        ];
+        let cube = Array3::from_shape_fn((4, 3, 5), |(x, y, t)| (100 * x + 10 * y + t) as i32);
+        file.add_dataset("Image", "ds1", traces.view())?;
+        file.add_dataset("Image", "ds2", cube.view())?;
+
+        let block: Array2<f64> = file.read_slice("Image", "ds1", s![1.., 2..4])?;
+        assert_eq!(block, traces.slice(s![1.., 2..4]));
+        let pixels: Array3<i32> = file.read_slice("Image", "ds2", s![1..3, 0..2, ..])?;
+        assert_eq!(pixels, cube.slice(s![1..3, 0..2, ..]));
+
+        assert_eq!(file.read_trace::<f64>("Image", "ds1", &[2])?, traces.row(2));
+        assert_eq!(
+            file.read_trace::<i32>("Image", "ds2", &[3, 1])?,
+            cube.slice(s![3, 1, ..])
+        );
+        assert!(file.read_trace::<i32>("Image", "ds2", &[3]).is_err());
+
+        assert_eq!(
+            file.read_time_window("Image", "ds1", 0.15, 0.35)?,
+            traces.slice(s![.., 2..4])
+        );
+        assert_eq!(
+            file.read_time_window("Image", "ds1", 1.0, 2.0)?.shape(),
+            &[3, 0]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_dataset() -> Result<(), Box<dyn std::error::Error>> {
+        use ndarray::{Ix1, Ix2, Ix3, IxDyn};
+
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("typed.thz"))?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let single: Array2<f32> = array![[0.0, 0.5], [1.5, -2.0]];
+        let counts: Array2<i32> = array![[1, 2], [3, 4]];
+        file.add_dataset("Measurement", "ds1", single.view())?;
+        file.add_dataset("Measurement", "ds2", counts.view())?;
+
+        assert_eq!(file.read_dataset::<f32, Ix2>("Measurement", "ds1")?, single);
+        assert_eq!(
+            file.read_dataset::<f64, Ix2>("Measurement", "ds1")?,
+            single.mapv(f64::from)
+        );
+        assert_eq!(
+            file.read_dataset::<f64, Ix2>("Measurement", "ds2")?,
+            counts.mapv(f64::from)
+        );
+        assert_eq!(
+            file.read_dataset::<i64, IxDyn>("Measurement", "ds2")?,
+            counts.mapv(i64::from).into_dyn()
+        );
+
+        // floats are not truncated to integers
+        assert!(file.read_dataset::<i32, Ix2>("Measurement", "ds1").is_err());
+        // dimensionality must match
+        let error = file
+            .read_dataset::<f64, Ix3>("Measurement", "ds1")
+            .unwrap_err();
+        assert!(error.to_string().contains("2 dimensions"));
+        assert!(file.read_dataset::<f64, Ix1>("Measurement", "ds1").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_delete_rename() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("manage.thz"))?;
+        let meta_data = DotthzMetaData {
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        file.add_group("Measurement", &meta_data)?;
+        let trace: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        file.add_dataset("Measurement", "ds1", trace.view())?;
+        file.add_dataset("Measurement", "ds2", trace.view())?;
+
+        // replacing keeps the description, upserting a new dataset appends one
+        let replaced = trace.mapv(|v| v * 2.0);
+        file.replace_dataset("Measurement", "ds2", replaced.view())?;
+        assert_eq!(
+            file.get_dataset("Measurement", "ds2")?.read_2d::<f64>()?,
+            replaced
+        );
+        assert!(file
+            .replace_dataset("Measurement", "ds9", trace.view())
+            .is_err());
+        file.upsert_dataset("Measurement", "ds3", trace.view(), "Sample 2")?;
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            vec!["Reference", "Sample", "Sample 2"]
+        );
+
+        // deleting a dataset in the middle leaves the others untouched until renumbered
+        let shifted = trace.mapv(|v| v + 1.0);
+        file.replace_dataset("Measurement", "ds3", shifted.view())?;
+        file.delete_dataset("Measurement", "ds2")?;
+        assert!(file.get_dataset("Measurement", "ds2").is_err());
+        assert_eq!(
+            file.get_meta_data("Measurement")?
+                .dataset_description("ds3"),
+            Some("Sample 2")
+        );
+        file.renumber_datasets("Measurement")?;
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            vec!["Reference", "Sample 2"]
+        );
+        assert_eq!(
+            file.get_dataset("Measurement", "ds2")?.read_2d::<f64>()?,
+            shifted
+        );
+        assert!(file.get_dataset("Measurement", "ds3").is_err());
+
+        // a dataset leaving `ds1` to `dsN` leaves a gap, one joining is appended
+        file.rename_dataset("Measurement", "ds1", "Reference")?;
+        assert!(file.get_dataset("Measurement", "ds2").is_ok());
+        file.renumber_datasets("Measurement")?;
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            vec!["Sample 2"]
+        );
+        assert!(file
+            .rename_dataset("Measurement", "Reference", "ds3")
+            .is_err());
+        file.rename_dataset("Measurement", "Reference", "ds2")?;
+        let meta = file.get_meta_data("Measurement")?;
+        assert_eq!(meta.ds_description, vec!["Sample 2", "Reference"]);
+        assert_eq!(meta.dataset_description("ds2"), Some("Reference"));
+        assert_eq!(
+            file.get_dataset("Measurement", "ds1")?.read_2d::<f64>()?,
+            shifted
+        );
+        assert!(file.rename_dataset("Measurement", "ds1", "ds2").is_err());
+        assert!(file
+            .upsert_dataset("Measurement", "ds9", trace.view(), "Gap")
+            .is_err());
+
+        file.rename_group("Measurement", "Renamed")?;
+        assert!(file.get_group("Measurement").is_err());
+        let updated = DotthzMetaData {
+            user: "Test User".to_string(),
+            ..meta.clone()
+        };
+        file.upsert_group("Renamed", &updated)?;
+        assert_eq!(file.get_meta_data("Renamed")?, updated);
+        file.upsert_group("New", &meta_data)?;
+        file.delete_group("Renamed")?;
+        assert_eq!(file.get_group_names()?, vec!["/New"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_keeps_attributes_and_axes() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("replace.thz"))?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let trace: Array2<f64> = array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0]];
+        file.add_described_dataset("Measurement", "Reference", trace.view())?;
+        let attributes = DatasetAttributes {
+            scale: Some(0.5),
+            ..DatasetAttributes::time_domain("ps", "nA")
+        };
+        file.set_dataset_attributes("Measurement", "ds1", &attributes)?;
+        file.add_axis("Measurement", "time", array![0.0, 1.0, 2.0].view())?;
+        file.attach_axis("Measurement", "ds1", 1, "time")?;
+        let reference_list = |file: &DotthzFile| {
+            file.get_dataset("Measurement", "time")
+                .and_then(|time| time.attr("REFERENCE_LIST"))
+                .map(|attr| attr.size())
+        };
+
+        // same shape and type is written in place
+        let doubled = trace.mapv(|v| v * 2.0);
+        file.replace_dataset("Measurement", "ds1", doubled.view())?;
+        assert_eq!(
+            file.get_dataset("Measurement", "ds1")?.read_2d::<f64>()?,
+            doubled
+        );
+        assert_eq!(
+            file.get_dataset_attributes("Measurement", "ds1")?,
+            attributes
+        );
+        assert!(file.get_axis("Measurement", "ds1", 1)?.is_some());
+
+        // another shape keeps the attributes and the axis where it still fits
+        let three_rows: Array2<f64> = array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        file.upsert_dataset("Measurement", "ds1", three_rows.view(), "Reference")?;
+        assert_eq!(
+            file.get_dataset_attributes("Measurement", "ds1")?,
+            attributes
+        );
+        assert_eq!(
+            file.read_axis::<f64>("Measurement", "ds1", 1)?,
+            array![0.0, 1.0, 2.0]
+        );
+        assert_eq!(reference_list(&file)?, 1);
+
+        // an axis that no longer fits is detached, nothing refers to the old dataset
+        let four_samples: Array2<f32> = array![[0.0, 0.1, 0.2, 0.3], [1.0, 2.0, 3.0, 4.0]];
+        file.replace_dataset("Measurement", "ds1", four_samples.view())?;
+        assert!(file.get_axis("Measurement", "ds1", 1)?.is_none());
+        assert!(reference_list(&file).is_err());
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            vec!["Reference"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_described_dataset() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("described.thz"))?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let reference: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        let sample: Array2<f64> = array![[0.0, 0.1], [0.5, 1.0]];
+
+        assert_eq!(
+            file.add_described_dataset("Measurement", "Reference", reference.view())?,
+            "ds1"
+        );
+        assert_eq!(
+            file.add_described_dataset("Measurement", "Sample", sample.view())?,
+            "ds2"
+        );
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            vec!["Reference", "Sample"]
+        );
+        assert_eq!(
+            file.get_dataset_by_description("Measurement", "Sample")?
+                .read_2d::<f64>()?,
+            sample
+        );
+        assert!(file
+            .get_dataset_by_description("Measurement", "Missing")
+            .is_err());
+
+        // a gap in the numbering is kept, the next name follows the highest `dsN`
+        file.delete_dataset("Measurement", "ds1")?;
+        assert_eq!(
+            file.add_described_dataset("Measurement", "Sample 2", sample.view())?,
+            "ds3"
+        );
+        assert!(file.get_dataset("Measurement", "ds1").is_err());
+        assert_eq!(
+            file.get_dataset("Measurement", "ds2")?.read_2d::<f64>()?,
+            sample
+        );
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            vec!["Reference", "Sample", "Sample 2"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dataset_attributes() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("attributes.thz"))?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let reference: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        file.add_described_dataset("Measurement", "Reference", reference.view())?;
+        assert!(file
+            .get_dataset_attributes("Measurement", "ds1")?
+            .is_empty());
+
+        let mut attributes = DatasetAttributes::time_domain("ps", "nA");
+        attributes.sampling_interval = Some(0.1);
+        attributes
+            .extra
+            .insert("detector".to_string(), "PCA".to_string());
+        file.set_dataset_attributes("Measurement", "ds1", &attributes)?;
+        let read = file.get_dataset_attributes("Measurement", "ds1")?;
+        assert_eq!(read, attributes);
+        assert_eq!(read.unit(0).and_then(si_factor), Some(1e-12));
+        assert_eq!(read.unit(1).and_then(si_factor), Some(1e-9));
+
+        attributes.sampling_interval = None;
+        file.set_dataset_attributes("Measurement", "ds1", &attributes)?;
+        assert_eq!(
+            file.get_dataset_attributes("Measurement", "ds1")?
+                .sampling_interval,
+            None
+        );
+
+        // offset and scale follow the CF names, the old names are still read
+        attributes.offset = Some(1.5);
+        attributes.scale = Some(2.0);
+        file.set_dataset_attributes("Measurement", "ds1", &attributes)?;
+        let dataset = file.get_dataset("Measurement", "ds1")?;
+        assert_eq!(dataset.attr("add_offset")?.read_scalar::<f64>()?, 1.5);
+        assert_eq!(dataset.attr("scale_factor")?.read_scalar::<f64>()?, 2.0);
+        dataset.delete_attr("scale_factor")?;
+        dataset
+            .new_attr::<f64>()
+            .create("scale")?
+            .write_scalar(&3.0)?;
+        assert_eq!(
+            file.get_dataset_attributes("Measurement", "ds1")?.scale,
+            Some(3.0)
+        );
+
+        // a reserved free-form key is rejected before anything is written
+        let mut invalid = DatasetAttributes::time_domain("fs", "mV");
+        invalid.extra.insert("scale".to_string(), "1".to_string());
+        assert!(file
+            .set_dataset_attributes("Measurement", "ds1", &invalid)
+            .is_err());
+        let read = file.get_dataset_attributes("Measurement", "ds1")?;
+        assert_eq!(read.units, vec!["ps", "nA"]);
+        assert_eq!(read.scale, Some(3.0));
+
+        let mut csv = Vec::new();
+        export_csv(&file, "Measurement", &mut csv, &CsvExportOptions::default())?;
+        let csv = String::from_utf8(csv)?;
+        assert!(csv.contains("Reference Time (ps)"));
+        assert!(csv.contains("Reference Signal (nA)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dimension_scales() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("scales.thz"))?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let reference: Array2<f64> = array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0]];
+        let sample: Array2<f64> = array![[0.0, 0.1, 0.2], [0.5, 1.0, 1.5]];
+        file.add_described_dataset("Measurement", "Reference", reference.view())?;
+        file.add_described_dataset("Measurement", "Sample", sample.view())?;
+
+        // without a scale the time row of the plain layout is the axis
+        assert_eq!(
+            file.read_axis::<f64>("Measurement", "ds1", 1)?,
+            array![0.0, 0.1, 0.2]
+        );
+
+        let time = array![0.0, 1.0, 2.0];
+        assert!(file.add_axis("Measurement", "ds3", time.view()).is_err());
+        file.add_axis("Measurement", "time", time.view())?;
+        assert!(is_dimension_scale(
+            &file.get_dataset("Measurement", "time")?
+        ));
+        assert_eq!(
+            file.attach_axis_to_group("Measurement", "time")?,
+            vec!["ds1", "ds2"]
+        );
+        assert_eq!(file.read_axis::<f64>("Measurement", "ds2", 1)?, time);
+        assert!(file.get_axis("Measurement", "ds2", 0)?.is_none());
+        assert!(file.read_axis::<f64>("Measurement", "ds2", 0).is_err());
+        // the axis is not part of `dsDescription`, new traces still get the next `dsN` name
+        assert_eq!(
+            file.get_meta_data("Measurement")?.ds_description,
+            vec!["Reference", "Sample"]
+        );
+        assert_eq!(
+            file.add_described_dataset("Measurement", "Sample 2", sample.view())?,
+            "ds3"
+        );
+        // axes are no `dsN` datasets, but not reported as such either
+        assert!(!validate(&file)?
+            .iter()
+            .any(|d| d.message.contains("`time`")));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "hdf5-sys-static"))]
+    fn test_dimension_scales_h5ds() -> Result<(), Box<dyn std::error::Error>> {
+        use hdf5_sys::h5i::hid_t;
+        use std::os::raw::{c_int, c_uint};
+
+        // the dimension scale API of the HDF5 high-level library, not bound by hdf5-sys
+        #[link(name = "hdf5_hl")]
+        extern "C" {
+            fn H5DSis_scale(did: hid_t) -> c_int;
+            fn H5DSis_attached(did: hid_t, dsid: hid_t, idx: c_uint) -> c_int;
+            fn H5DSget_num_scales(did: hid_t, idx: c_uint) -> c_int;
+        }
+
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("h5ds.thz"))?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let trace: Array2<f64> = array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0]];
+        file.add_described_dataset("Measurement", "Reference", trace.view())?;
+        file.add_described_dataset("Measurement", "Sample", trace.view())?;
+        file.add_axis("Measurement", "time", array![0.0, 1.0, 2.0].view())?;
+        file.attach_axis_to_group("Measurement", "time")?;
+
+        // the hand-written attributes are understood by the HDF5 library
+        let time = file.get_dataset("Measurement", "time")?;
+        let ds1 = file.get_dataset("Measurement", "ds1")?;
+        let ds2 = file.get_dataset("Measurement", "ds2")?;
+        unsafe {
+            assert!(H5DSis_scale(time.id()) > 0);
+            assert!(H5DSis_scale(ds1.id()) == 0);
+            assert_eq!(H5DSget_num_scales(ds1.id(), 0), 0);
+            assert_eq!(H5DSget_num_scales(ds1.id(), 1), 1);
+            assert!(H5DSis_attached(ds1.id(), time.id(), 1) > 0);
+            assert!(H5DSis_attached(ds2.id(), time.id(), 1) > 0);
+        }
+
+        // deleting a dataset removes it from the `REFERENCE_LIST` of its axis
+        drop(ds1);
+        file.delete_dataset("Measurement", "ds1")?;
+        assert_eq!(time.attr("REFERENCE_LIST")?.size(), 1);
+        unsafe {
+            assert!(H5DSis_attached(ds2.id(), time.id(), 1) > 0);
+        }
+        // renaming keeps the axis attached, references do not depend on names
+        file.rename_dataset("Measurement", "ds2", "Renamed")?;
+        assert_eq!(
+            file.read_axis::<f64>("Measurement", "Renamed", 1)?,
+            array![0.0, 1.0, 2.0]
+        );
+        file.delete_dataset("Measurement", "Renamed")?;
+        assert!(time.attr("REFERENCE_LIST").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mixed_described_datasets() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("mixed.thz"))?;
+        let reference: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        let sample: Array2<f64> = array![[0.0, 0.1], [0.5, 1.0]];
+
+        // datasets added without a description are described by their name
+        file.add_group("Plain", &DotthzMetaData::default())?;
+        file.add_dataset("Plain", "ds1", reference.view())?;
+        assert_eq!(
+            file.add_described_dataset("Plain", "Sample", sample.view())?,
+            "ds2"
+        );
+        let meta_data = file.get_meta_data("Plain")?;
+        assert_eq!(meta_data.ds_description, vec!["ds1", "Sample"]);
+        assert_eq!(meta_data.dataset_description("ds2"), Some("Sample"));
+        assert_eq!(
+            file.get_dataset_by_description("Plain", "Sample")?
+                .read_2d::<f64>()?,
+            sample
+        );
+
+        // entries filled in before their dataset exists are kept
+        let meta_data = DotthzMetaData {
+            ds_description: vec![
+                "Reference".to_string(),
+                "Sample".to_string(),
+                "Sample 2".to_string(),
+            ],
+            ..Default::default()
+        };
+        file.add_group("Prefilled", &meta_data)?;
+        file.add_dataset("Prefilled", "ds1", reference.view())?;
+        assert_eq!(
+            file.add_described_dataset("Prefilled", "Sample", sample.view())?,
+            "ds2"
+        );
+        assert_eq!(
+            file.get_meta_data("Prefilled")?.ds_description,
+            vec!["Reference", "Sample", "Sample 2"]
+        );
+        assert_eq!(
+            file.get_dataset_by_description("Prefilled", "Reference")?
+                .read_2d::<f64>()?,
+            reference
+        );
+        assert!(file
+            .get_dataset_by_description("Prefilled", "Sample 2")
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_netcdf() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("measurement.thz"))?;
+        file.add_group("Measurement", &DotthzMetaData::default())?;
+        let reference: Array2<f64> = array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0]];
+        let sample: Array2<f64> = array![[0.0, 0.1, 0.2], [0.5, 1.0, 1.5]];
+        file.add_described_dataset("Measurement", "Reference", reference.view())?;
+        file.add_described_dataset("Measurement", "Sample", sample.view())?;
+        file.set_dataset_attributes(
+            "Measurement",
+            "ds1",
+            &DatasetAttributes::time_domain("ps", "nA"),
+        )?;
+
+        let out = export_netcdf(&file, dir.path().join("netcdf.thz"))?;
+        assert_eq!(
+            out.read_axis::<f64>("Measurement", "ds2", 1)?,
+            array![0.0, 0.1, 0.2]
+        );
+        assert_eq!(
+            out.get_axis("Measurement", "ds1", 1)?.map(|a| a.name()),
+            Some("/Measurement/time".to_string())
+        );
+        assert_eq!(
+            out.get_axis("Measurement", "ds1", 0)?.map(|a| a.name()),
+            Some("/Measurement/row".to_string())
+        );
+        let time = out.get_dataset_attributes("Measurement", "time")?;
+        assert_eq!(time.units, vec!["ps"]);
+        let ds1 = out.get_dataset("Measurement", "ds1")?;
+        assert_eq!(
+            ds1.attr("long_name")?
+                .read_scalar::<hdf5::types::VarLenUnicode>()?
+                .as_str(),
+            "Reference"
+        );
+        assert_eq!(
+            ds1.attr("units")?
+                .read_scalar::<hdf5::types::VarLenUnicode>()?
+                .as_str(),
+            "nA"
+        );
+        assert_eq!(out.read_dataset::<f64, _>("Measurement", "ds1")?, reference);
+        // the exported file is still a valid dotThz file
+        assert!(!validate(&out)?
+            .iter()
+            .any(|d| d.message.contains("dsDescription")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_groups() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("campaign.thz"))?;
+        let mut campaign = DotthzMetaData {
+            user: "John Doe".to_string(),
+            instrument: "TeraSpec".to_string(),
+            version: "1.00".to_string(),
+            ..Default::default()
+        };
+        campaign
+            .md
+            .insert("Humidity (%)".to_string(), "5".to_string());
+        file.add_group("Campaign", &campaign)?;
+
+        let mut measurement = DotthzMetaData {
+            instrument: "TeraSpec 2".to_string(),
+            ..Default::default()
+        };
+        measurement
+            .md
+            .insert("Temperature (K)".to_string(), "300".to_string());
+        file.add_group("Campaign/Sample A/300K", &measurement)?;
+        let data: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        file.add_described_dataset("Campaign/Sample A/300K", "Sample", data.view())?;
+        file.add_group("Campaign/Sample A/77K", &DotthzMetaData::default())?;
+
+        assert_eq!(file.get_group_names()?, vec!["/Campaign"]);
+        assert_eq!(
+            file.get_measurement_group_names()?,
+            vec!["/Campaign/Sample A/300K", "/Campaign/Sample A/77K"]
+        );
+
+        let inherited = file.get_inherited_meta_data("Campaign/Sample A/300K")?;
+        assert_eq!(inherited.user, "John Doe");
+        assert_eq!(inherited.instrument, "TeraSpec 2");
+        assert_eq!(inherited.version, "1.00");
+        assert_eq!(inherited.md["Humidity (%)"], "5");
+        assert_eq!(inherited.md["Temperature (K)"], "300");
+        assert_eq!(inherited.ds_description, vec!["Sample"]);
+        assert!(file
+            .get_inherited_meta_data("Campaign/Sample A/77K")?
+            .ds_description
+            .is_empty());
+
+        // groups that only hold other groups are not validated as measurements
+        assert!(!validate(&file)?
+            .iter()
+            .any(|d| d.group.as_deref() == Some("/Campaign/Sample A")));
+
+        // exports carry the inherited meta-data
+        let options = CsvExportOptions {
+            meta_data_preamble: true,
+            ..Default::default()
+        };
+        let mut csv = Vec::new();
+        export_csv(&file, "Campaign/Sample A/300K", &mut csv, &options)?;
+        assert!(String::from_utf8(csv)?.contains("John Doe"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_netcdf_dimensions() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("measurement.thz"))?;
+        let group = "Campaign/Sample";
+        file.add_group(group, &DotthzMetaData::default())?;
+        let square: Array2<f64> = array![[0.0, 0.1, 0.2], [1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        file.add_described_dataset(group, "Square", square.view())?;
+        let cube = ndarray::Array3::<f64>::zeros((2, 3, 3));
+        file.add_described_dataset(group, "Cube", cube.view())?;
+        file.add_axis(group, "frequency", array![1e12, 2e12, 3e12].view())?;
+        file.attach_axis(group, "ds2", 2, "frequency")?;
+        // noise parameters start with a frequency row, they are no time-domain trace
+        let noise = ndarray::Array2::<f64>::zeros((5, 3));
+        file.add_described_dataset(group, NOISE_DESCRIPTION, noise.view())?;
+        let attributes = DatasetAttributes {
+            units: vec!["Hz".to_string()],
+            labels: vec!["Frequency".to_string()],
+            ..Default::default()
+        };
+        file.set_dataset_attributes(group, "ds3", &attributes)?;
+
+        let out = export_netcdf(&file, dir.path().join("netcdf.thz"))?;
+        let axis_name = |dataset: &str, dim: usize| -> Result<_, Box<dyn std::error::Error>> {
+            Ok(out.get_axis(group, dataset, dim)?.map(|a| a.name()))
+        };
+        // the dimensions of an (N, N) trace stay distinct
+        assert_eq!(
+            axis_name("ds1", 0)?,
+            Some("/Campaign/Sample/row".to_string())
+        );
+        assert_eq!(
+            axis_name("ds1", 1)?,
+            Some("/Campaign/Sample/time".to_string())
+        );
+        // other dimensions are named per dataset, existing scales are kept as coordinates
+        assert_eq!(
+            axis_name("ds2", 0)?,
+            Some("/Campaign/Sample/ds2_dim0".to_string())
+        );
+        assert_eq!(
+            axis_name("ds2", 1)?,
+            Some("/Campaign/Sample/ds2_dim1".to_string())
+        );
+        assert_eq!(
+            axis_name("ds2", 2)?,
+            Some("/Campaign/Sample/frequency".to_string())
+        );
+        assert!(is_dimension_scale(&out.get_dataset(group, "frequency")?));
+        assert_eq!(
+            out.read_axis::<f64>(group, "ds2", 2)?,
+            array![1e12, 2e12, 3e12]
+        );
+        assert_eq!(
+            axis_name("ds3", 0)?,
+            Some("/Campaign/Sample/ds3_dim0".to_string())
+        );
+        assert_eq!(
+            axis_name("ds3", 1)?,
+            Some("/Campaign/Sample/ds3_dim1".to_string())
+        );
+        assert!(out
+            .get_dataset(group, "time")?
+            .attr("axis")?
+            .read_scalar::<hdf5::types::VarLenUnicode>()
+            .is_ok_and(|axis| axis.as_str() == "T"));
+
+        // every dimension has a unique netCDF dimension ID
+        let mut ids = out
+            .get_datasets(group)?
+            .iter()
+            .filter(|dataset| is_dimension_scale(dataset))
+            .map(|dataset| dataset.attr("_Netcdf4Dimid")?.read_scalar::<i32>())
+            .collect::<hdf5::Result<Vec<i32>>>()?;
+        ids.sort();
+        assert_eq!(ids, (0..ids.len() as i32).collect::<Vec<i32>>());
+
+        // the parent group holds no measurement, it gets no dotThz attributes
+        assert!(out.get_group("Campaign")?.attr_names()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_wrapper() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("group.thz"))?;
+        let mut meta_data = DotthzMetaData {
+            user: "John Doe".to_string(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            ..Default::default()
+        };
+        meta_data
+            .md
+            .insert("Thickness (mm)".to_string(), "1.5".to_string());
+        meta_data
+            .md
+            .insert("Sample".to_string(), "Silicon".to_string());
+        let group = file.add_group("Measurement", &meta_data)?;
+        assert_eq!(group.name(), "/Measurement");
+        assert_eq!(group.meta_data()?, meta_data);
+
+        let data: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        for name in ["ds10", "ds2", "ds1"] {
+            group.add_dataset(name, data.view())?;
+        }
+        let names = group
+            .datasets()?
+            .iter()
+            .map(|d| d.name())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            names,
+            ["/Measurement/ds1", "/Measurement/ds2", "/Measurement/ds10"]
+        );
+        assert_eq!(group.dataset("ds2")?.read_2d::<f64>()?, data);
+
+        meta_data.md.shift_remove("Sample");
+        let group = file.get_group("Measurement")?;
+        group.set_meta_data(&meta_data)?;
+        assert_eq!(file.get_meta_data("Measurement")?, meta_data);
+        assert!(group.attr("md2").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_traversal() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("traversal.thz"))?;
+        let data: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        for (name, mode) in [("A", "transmission"), ("B", "reflection"), ("C/D", "")] {
+            let meta_data = DotthzMetaData {
+                mode: mode.to_string(),
+                ..Default::default()
+            };
+            file.add_group(name, &meta_data)?;
+            file.add_described_dataset(name, "Reference", data.view())?;
+            file.add_described_dataset(name, "Sample", data.view())?;
+        }
+        // the mode of `C/D` is inherited from its parent
+        let parent = DotthzMetaData {
+            mode: "reflection".to_string(),
+            ..Default::default()
+        };
+        file.set_meta_data("C", &parent)?;
+
+        let reflection = file
+            .groups_iter()?
+            .filter_map(|group| group.ok())
+            .filter(|(_, meta_data, _)| meta_data.mode == "reflection")
+            .map(|(name, _, _)| name)
+            .collect::<Vec<String>>();
+        assert_eq!(reflection, vec!["/B", "/C/D"]);
+
+        let (_, _, group) = file.groups_iter()?.next().unwrap()?;
+        let datasets = group
+            .datasets_iter()?
+            .map(|dataset| dataset.map(|(name, description, _)| (name, description)))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            datasets,
+            vec![
+                ("ds1".to_string(), Some("Reference".to_string())),
+                ("ds2".to_string(), Some("Sample".to_string()))
+            ]
+        );
+
+        struct SampleCounter {
+            samples: Vec<String>,
+        }
+        impl DotthzVisitor for SampleCounter {
+            fn visit_group(
+                &mut self,
+                name: &str,
+                _meta_data: &DotthzMetaData,
+                _group: &DotthzGroup,
+            ) -> Result<bool, Box<dyn std::error::Error>> {
+                Ok(name != "/A")
+            }
+
+            fn visit_dataset(
+                &mut self,
+                group: &DotthzGroup,
+                name: &str,
+                description: Option<&str>,
+                _dataset: &Dataset,
+            ) -> Result<(), Box<dyn std::error::Error>> {
+                if description == Some("Sample") {
+                    self.samples.push(format!("{}/{}", group.name(), name));
+                }
+                Ok(())
+            }
+        }
+        let mut counter = SampleCounter {
+            samples: Vec::new(),
+        };
+        file.visit(&mut counter)?;
+        assert_eq!(counter.samples, vec!["/B/ds2", "/C/D/ds2"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_backend() -> Result<(), Box<dyn std::error::Error>> {
+        check_backend(&mut MemoryBackend::new())
+    }
+
+    #[test]
+    fn test_hdf5_backend() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let mut file = DotthzFile::create(&dir.path().join("backend.thz"))?;
+        check_backend(file.backend_mut())
+    }
+
+    #[test]
+    fn test_memory_file() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = DotthzFile::with_backend(MemoryBackend::new());
+        let mut meta_data = DotthzMetaData {
+            user: "Jane".to_string(),
+            ..Default::default()
+        };
+        meta_data
+            .md
+            .insert("Thickness (mm)".to_string(), "0.5".to_string());
+        file.create_group("campaign", &meta_data)?;
+
+        meta_data.user = String::new();
+        meta_data.ds_description = vec!["Reference".to_string(), "Sample".to_string()];
+        file.create_group("campaign/sample/300K", &meta_data)?;
+        file.add_f64_dataset(
+            "campaign/sample/300K",
+            "ds1",
+            array![[0.0, 1.0], [2.0, 3.0]].into_dyn().view(),
+        )?;
+
+        assert_eq!(file.get_group_names()?, vec!["/campaign"]);
+        assert_eq!(
+            file.get_measurement_group_names()?,
+            vec!["/campaign/sample/300K"]
+        );
+        assert_eq!(
+            file.get_dataset_names("campaign/sample/300K")?,
+            vec!["/campaign/sample/300K/ds1"]
+        );
+        assert_eq!(
+            file.read_f64_dataset("/campaign/sample/300K", "ds1")?,
+            array![[0.0, 1.0], [2.0, 3.0]].into_dyn()
+        );
+
+        let inherited = file.get_inherited_meta_data("campaign/sample/300K")?;
+        assert_eq!(inherited.user, "Jane");
+        assert_eq!(inherited.ds_description, meta_data.ds_description);
+
+        meta_data.md.clear();
+        file.set_meta_data("campaign/sample/300K", &meta_data)?;
+        assert!(file.get_meta_data("campaign/sample/300K")?.md.is_empty());
+        assert!(!file
+            .backend()
+            .attr_names("campaign/sample/300K")?
+            .contains(&"md1".to_string()));
+
+        file.delete_group("campaign/sample")?;
+        assert_eq!(file.get_measurement_group_names()?, vec!["/campaign"]);
+        assert!(!file.into_backend().has_group("campaign/sample/300K"));
+        Ok(())
+    }
+
+    /// Shared by the backend tests, so both backends behave the same.
+    fn check_backend<B: Backend>(backend: &mut B) -> Result<(), Box<dyn std::error::Error>> {
+        backend.create_group("Measurement")?;
+        backend.create_group("/Measurement/Sample")?;
+        assert!(backend.create_group("Missing/Group").is_err());
+        assert_eq!(backend.group_names("/")?, vec!["/Measurement"]);
+        assert_eq!(
+            backend.group_names("/Measurement")?,
+            vec!["/Measurement/Sample"]
+        );
+
+        let mut meta_data = DotthzMetaData {
+            user: "Test User".to_string(),
+            orcid: "0000-0001-2345-6789".to_string(),
+            mode: "transmission".to_string(),
+            md: [
+                ("Thickness (mm)".to_string(), "0.52".to_string()),
+                ("Sample".to_string(), "PVDF".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            ds_description: vec!["Reference".to_string(), "Sample".to_string()],
+            ..Default::default()
+        };
+        backend.set_meta_data("Measurement", &meta_data)?;
+        assert_eq!(
+            backend.read_attr("Measurement", "md1")?,
+            Some(AttributeValue::Text(vec!["0.52".to_string()]))
+        );
+        assert_eq!(backend.get_meta_data("Measurement")?, meta_data);
+
+        // changed and dropped entries are recreated as strings
+        meta_data.md.shift_remove("Sample");
+        meta_data
+            .md
+            .insert("Thickness (mm)".to_string(), "0.5".to_string());
+        backend.update_meta_data("Measurement", &meta_data)?;
+        assert!(backend.read_attr("Measurement", "md2")?.is_none());
+        assert_eq!(
+            backend.read_attr("Measurement", "md1")?,
+            Some(AttributeValue::Text(vec!["0.5".to_string()]))
+        );
+
+        // attributes keep their type and shape
+        backend.set_meta_data("Measurement", &meta_data)?;
+        assert_eq!(
+            backend.read_attr("Measurement", "md1")?,
+            Some(AttributeValue::Text(vec!["0.5".to_string()]))
+        );
+        assert!(backend
+            .write_attr("Measurement", "md1", &AttributeValue::Number(0.5))
+            .is_err());
+        assert!(backend
+            .write_attr(
+                "Measurement",
+                "md1",
+                &AttributeValue::Text(vec!["0.5".to_string(), "0.6".to_string()])
+            )
+            .is_err());
+        backend.delete_attr("Measurement", "md1")?;
+        backend.write_attr("Measurement", "md1", &AttributeValue::Number(0.52))?;
+        assert!(backend
+            .write_attr(
+                "Measurement",
+                "md1",
+                &AttributeValue::Text(vec!["0.52".to_string()])
+            )
+            .is_err());
+
+        // existing numeric attributes store numbers as numbers
+        backend.set_meta_data("Measurement", &meta_data)?;
+        assert_eq!(
+            backend.read_attr("Measurement", "md1")?,
+            Some(AttributeValue::Number(0.5))
+        );
+        assert_eq!(backend.get_meta_data("Measurement")?, meta_data);
+
+        // and stay numbers when moved to another `mdN` attribute or renamed
+        let mut shifted = meta_data.clone();
+        shifted
+            .md
+            .shift_insert(0, "Sample".to_string(), "PVDF".to_string());
+        backend.update_meta_data("Measurement", &shifted)?;
+        assert_eq!(
+            backend.read_attr("Measurement", "md2")?,
+            Some(AttributeValue::Number(0.5))
+        );
+        let mut renamed = DotthzMetaData::default();
+        renamed.md.insert("Sample".to_string(), "PVDF".to_string());
+        renamed
+            .md
+            .insert("Thickness (um)".to_string(), "0.5".to_string());
+        backend.update_meta_data("Measurement", &renamed)?;
+        assert_eq!(
+            backend.read_attr("Measurement", "md2")?,
+            Some(AttributeValue::Number(0.5))
+        );
+        backend.update_meta_data("Measurement", &shifted)?;
+        backend.update_meta_data("Measurement", &meta_data)?;
+        assert_eq!(
+            backend.read_attr("Measurement", "md1")?,
+            Some(AttributeValue::Number(0.5))
+        );
+        assert!(backend.read_attr("Measurement", "md2")?.is_none());
+        assert_eq!(backend.get_meta_data("Measurement")?, meta_data);
+
+        // and are recreated as strings for values that are not numbers
+        meta_data
+            .md
+            .insert("Thickness (mm)".to_string(), "thin".to_string());
+        backend.set_meta_data("Measurement", &meta_data)?;
+        assert_eq!(
+            backend.read_attr("Measurement", "md1")?,
+            Some(AttributeValue::Text(vec!["thin".to_string()]))
+        );
+        assert_eq!(backend.get_meta_data("Measurement")?, meta_data);
+        assert!(backend.get_meta_data("Missing").is_err());
+
+        let data: Array2<f64> = array![[0.0, 0.1], [1.0, 2.0]];
+        backend.write_dataset("Measurement", "ds1", data.view().into_dyn())?;
+        assert!(backend
+            .write_dataset("Measurement", "ds1", data.view().into_dyn())
+            .is_err());
+        assert_eq!(backend.dataset_names("Measurement")?, vec!["ds1"]);
+        assert_eq!(backend.read_dataset("Measurement", "ds1")?, data.into_dyn());
+
+        backend.delete_group("Measurement")?;
+        assert!(!backend.has_group("/Measurement/Sample"));
+        assert!(backend.group_names("/")?.is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,577 @@
+use crate::{DotthzFile, DotthzMetaData, TypedArray};
+use flate2::read::ZlibDecoder;
+use ndarray::{ArrayD, IxDyn, ShapeBuilder};
+use num_complex::Complex;
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+// data element types
+const MI_INT8: u32 = 1;
+const MI_UINT8: u32 = 2;
+const MI_INT16: u32 = 3;
+const MI_UINT16: u32 = 4;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_SINGLE: u32 = 7;
+const MI_DOUBLE: u32 = 9;
+const MI_INT64: u32 = 12;
+const MI_UINT64: u32 = 13;
+const MI_MATRIX: u32 = 14;
+const MI_COMPRESSED: u32 = 15;
+const MI_UTF8: u32 = 16;
+const MI_UTF16: u32 = 17;
+
+// array classes
+const MX_STRUCT_CLASS: u8 = 2;
+const MX_CHAR_CLASS: u8 = 4;
+const MX_DOUBLE_CLASS: u8 = 6;
+const MX_SINGLE_CLASS: u8 = 7;
+const MX_INT32_CLASS: u8 = 12;
+const MX_INT64_CLASS: u8 = 14;
+const MX_UINT64_CLASS: u8 = 15;
+
+const COMPLEX_FLAG: u32 = 0x0800;
+
+/// Maximum length of variable and field names, excluding the terminating null byte.
+const MAX_NAME_LENGTH: usize = 63;
+
+/// A MATLAB value, numeric arrays are kept in row-major order and transposed when encoded.
+#[derive(Debug, Clone, PartialEq)]
+enum MatValue {
+    Numeric(TypedArray),
+    Char(String),
+    Struct(Vec<(String, MatValue)>),
+}
+
+/// Write every measurement group of `file` to a MATLAB v5 `.mat` file.
+///
+/// Each measurement group, including nested ones, becomes a struct variable named after the
+/// group path, holding one field per dataset and a `meta` struct with the meta-data inherited
+/// from its parents (see [`DotthzFile::get_inherited_meta_data`]). `md` entries are stored in the nested struct
+/// `meta.md`; as MATLAB field names are restricted to identifiers, the original keys are kept
+/// in `meta.md_description`. `f32`, `i32` and `i64` datasets are written as `single`, `int32`
+/// and `int64`, native complex datasets as complex `single` or `double`, all others as `double`.
+/// MATLAB has no 1D arrays, so 1D datasets are written as `1xN` row vectors.
+pub fn export_mat<P: AsRef<Path>>(file: &DotthzFile, path: P) -> Result<(), Box<dyn Error>> {
+    let mut out = header();
+    let mut variable_names = Vec::new();
+    for group_name in file.get_measurement_group_names()? {
+        let meta_data = file.get_inherited_meta_data(&group_name)?;
+        let group_name = group_name.trim_start_matches('/');
+
+        let mut field_names = vec!["meta".to_string()];
+        let mut fields = vec![("meta".to_string(), meta_data_struct(group_name, &meta_data))];
+        for dataset in file.get_datasets(group_name)? {
+            let name = dataset.name();
+            let name = unique_name(name.rsplit('/').next().unwrap_or(&name), &mut field_names);
+            fields.push((name, MatValue::Numeric(TypedArray::read(&dataset)?)));
+        }
+
+        let variable_name = unique_name(group_name, &mut variable_names);
+        out.extend(encode_matrix(&variable_name, &MatValue::Struct(fields)));
+    }
+    fs::write(path, out)?;
+    Ok(())
+}
+
+/// Read the groups written by [`export_mat`] back into `file`.
+///
+/// Every struct variable with a `meta` field becomes a group, named after `meta.group` if set.
+/// Other variables are ignored. Compressed (`-v7`) files as saved by MATLAB are supported,
+/// `single`, `int32` and `int64` fields are imported as `f32`, `i32` and `i64` datasets and all
+/// other numeric fields as `f64`. Complex fields are imported as complex datasets, see
+/// [`DotthzFile::add_complex_dataset`].
+pub fn import_mat<P: AsRef<Path>>(file: &mut DotthzFile, path: P) -> Result<(), Box<dyn Error>> {
+    for (variable_name, value) in read_mat(&fs::read(path)?)? {
+        let MatValue::Struct(fields) = value else {
+            continue;
+        };
+        let Some(MatValue::Struct(meta)) = field(&fields, "meta") else {
+            continue;
+        };
+
+        let meta_data = meta_data_from_struct(meta);
+        let group_name = match field(meta, "group") {
+            Some(MatValue::Char(name)) if !name.is_empty() => name.clone(),
+            _ => variable_name,
+        };
+        file.add_group(&group_name, &meta_data)?;
+
+        for (name, value) in fields.iter().filter(|(name, _)| name != "meta") {
+            if let MatValue::Numeric(data) = value {
+                data.write_into(file, &group_name, name)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn meta_data_struct(group_name: &str, meta_data: &DotthzMetaData) -> MatValue {
+    let text = |s: &str| MatValue::Char(s.to_string());
+    let mut md_names = Vec::new();
+    let md = meta_data
+        .md
+        .iter()
+        .map(|(key, value)| (unique_name(key, &mut md_names), text(value)))
+        .collect();
+    let md_description = meta_data.md.keys().cloned().collect::<Vec<_>>().join(", ");
+    MatValue::Struct(vec![
+        ("group".to_string(), text(group_name)),
+        ("user".to_string(), text(&meta_data.user)),
+        ("email".to_string(), text(&meta_data.email)),
+        ("orcid".to_string(), text(&meta_data.orcid)),
+        ("institution".to_string(), text(&meta_data.institution)),
+        ("description".to_string(), text(&meta_data.description)),
+        ("version".to_string(), text(&meta_data.version)),
+        ("mode".to_string(), text(&meta_data.mode)),
+        ("instrument".to_string(), text(&meta_data.instrument)),
+        ("time".to_string(), text(&meta_data.time)),
+        ("date".to_string(), text(&meta_data.date)),
+        (
+            "ds_description".to_string(),
+            text(&meta_data.ds_description.join(", ")),
+        ),
+        ("md_description".to_string(), text(&md_description)),
+        ("md".to_string(), MatValue::Struct(md)),
+    ])
+}
+
+fn meta_data_from_struct(meta: &[(String, MatValue)]) -> DotthzMetaData {
+    let text = |name: &str| match field(meta, name) {
+        Some(MatValue::Char(s)) => s.clone(),
+        Some(MatValue::Numeric(data)) => scalar_text(data).unwrap_or_default(),
+        _ => String::new(),
+    };
+    let list = |name: &str| {
+        let s = text(name);
+        if s.is_empty() {
+            Vec::new()
+        } else {
+            s.split(", ").map(String::from).collect::<Vec<String>>()
+        }
+    };
+
+    let mut meta_data = DotthzMetaData {
+        user: text("user"),
+        email: text("email"),
+        orcid: text("orcid"),
+        institution: text("institution"),
+        description: text("description"),
+        version: text("version"),
+        mode: text("mode"),
+        instrument: text("instrument"),
+        time: text("time"),
+        date: text("date"),
+        ds_description: list("ds_description"),
+        ..Default::default()
+    };
+
+    if let Some(MatValue::Struct(md)) = field(meta, "md") {
+        // field names are sanitized, the original keys are listed in order in `md_description`
+        let keys = list("md_description");
+        for (i, (name, value)) in md.iter().enumerate() {
+            let key = keys.get(i).cloned().unwrap_or_else(|| name.clone());
+            let value = match value {
+                MatValue::Char(s) => s.clone(),
+                MatValue::Numeric(data) => match scalar_text(data) {
+                    Some(value) => value,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            meta_data.md.insert(key, value);
+        }
+    }
+    meta_data
+}
+
+/// The value of a numeric array holding a single element as text.
+fn scalar_text(data: &TypedArray) -> Option<String> {
+    if data.len() != 1 {
+        return None;
+    }
+    Some(match data {
+        TypedArray::F32(data) => data.iter().next()?.to_string(),
+        TypedArray::F64(data) => data.iter().next()?.to_string(),
+        TypedArray::I32(data) => data.iter().next()?.to_string(),
+        TypedArray::I64(data) => data.iter().next()?.to_string(),
+        TypedArray::C32(data) => data.iter().next()?.to_string(),
+        TypedArray::C64(data) => data.iter().next()?.to_string(),
+    })
+}
+
+fn field<'a>(fields: &'a [(String, MatValue)], name: &str) -> Option<&'a MatValue> {
+    fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+}
+
+/// Turn `name` into a valid MATLAB identifier that is not yet in `taken`.
+fn unique_name(name: &str, taken: &mut Vec<String>) -> String {
+    let mut base = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if !base.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        base.insert(0, 'x');
+    }
+    base.truncate(MAX_NAME_LENGTH - 4);
+
+    let mut candidate = base.clone();
+    let mut i = 2;
+    while taken.contains(&candidate) {
+        candidate = format!("{}_{}", base, i);
+        i += 1;
+    }
+    taken.push(candidate.clone());
+    candidate
+}
+
+fn matlab_dims(shape: &[usize]) -> Vec<usize> {
+    match shape.len() {
+        0 => vec![1, 1],
+        1 => vec![1, shape[0]],
+        _ => shape.to_vec(),
+    }
+}
+
+fn column_major<T: Copy>(data: &ArrayD<T>) -> Vec<T> {
+    data.t().iter().copied().collect()
+}
+
+fn from_column_major<T: Clone>(dims: &[usize], data: Vec<T>) -> Result<ArrayD<T>, Box<dyn Error>> {
+    let data = ArrayD::from_shape_vec(IxDyn(dims).f(), data)?;
+    Ok(data.as_standard_layout().into_owned())
+}
+
+fn header() -> Vec<u8> {
+    let mut header = format!(
+        "MATLAB 5.0 MAT-file, Platform: {}, Created by: dotthz-rs {}",
+        std::env::consts::OS,
+        env!("CARGO_PKG_VERSION")
+    )
+    .into_bytes();
+    header.resize(116, b' ');
+    // no subsystem data
+    header.extend([0u8; 8]);
+    header.extend(0x0100u16.to_le_bytes());
+    header.extend(b"IM");
+    header
+}
+
+fn write_element(out: &mut Vec<u8>, mi_type: u32, data: &[u8]) {
+    out.extend(mi_type.to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out.extend(data);
+    out.resize(out.len().next_multiple_of(8), 0);
+}
+
+fn encode_matrix(name: &str, value: &MatValue) -> Vec<u8> {
+    let (class, dims) = match value {
+        MatValue::Numeric(data) => {
+            let class = match data {
+                TypedArray::F32(_) | TypedArray::C32(_) => MX_SINGLE_CLASS,
+                TypedArray::F64(_) | TypedArray::C64(_) => MX_DOUBLE_CLASS,
+                TypedArray::I32(_) => MX_INT32_CLASS,
+                TypedArray::I64(_) => MX_INT64_CLASS,
+            };
+            (class, matlab_dims(data.shape()))
+        }
+        MatValue::Char(s) => (MX_CHAR_CLASS, vec![1, s.encode_utf16().count()]),
+        MatValue::Struct(_) => (MX_STRUCT_CLASS, vec![1, 1]),
+    };
+
+    let mut body = Vec::new();
+    let complex = matches!(
+        value,
+        MatValue::Numeric(TypedArray::C32(_) | TypedArray::C64(_))
+    );
+    let flags = [u32::from(class) | if complex { COMPLEX_FLAG } else { 0 }, 0];
+    write_element(&mut body, MI_UINT32, &le_bytes(&flags, u32::to_le_bytes));
+    let dims = dims.iter().map(|&d| d as i32).collect::<Vec<i32>>();
+    write_element(&mut body, MI_INT32, &le_bytes(&dims, i32::to_le_bytes));
+    write_element(&mut body, MI_INT8, name.as_bytes());
+
+    match value {
+        MatValue::Numeric(TypedArray::F32(data)) => write_element(
+            &mut body,
+            MI_SINGLE,
+            &le_bytes(&column_major(data), f32::to_le_bytes),
+        ),
+        MatValue::Numeric(TypedArray::F64(data)) => write_element(
+            &mut body,
+            MI_DOUBLE,
+            &le_bytes(&column_major(data), f64::to_le_bytes),
+        ),
+        MatValue::Numeric(TypedArray::I32(data)) => write_element(
+            &mut body,
+            MI_INT32,
+            &le_bytes(&column_major(data), i32::to_le_bytes),
+        ),
+        MatValue::Numeric(TypedArray::I64(data)) => write_element(
+            &mut body,
+            MI_INT64,
+            &le_bytes(&column_major(data), i64::to_le_bytes),
+        ),
+        // the real part is followed by the imaginary part
+        MatValue::Numeric(TypedArray::C32(data)) => {
+            let values = column_major(data);
+            let re = values.iter().map(|c| c.re).collect::<Vec<f32>>();
+            let im = values.iter().map(|c| c.im).collect::<Vec<f32>>();
+            write_element(&mut body, MI_SINGLE, &le_bytes(&re, f32::to_le_bytes));
+            write_element(&mut body, MI_SINGLE, &le_bytes(&im, f32::to_le_bytes));
+        }
+        MatValue::Numeric(TypedArray::C64(data)) => {
+            let values = column_major(data);
+            let re = values.iter().map(|c| c.re).collect::<Vec<f64>>();
+            let im = values.iter().map(|c| c.im).collect::<Vec<f64>>();
+            write_element(&mut body, MI_DOUBLE, &le_bytes(&re, f64::to_le_bytes));
+            write_element(&mut body, MI_DOUBLE, &le_bytes(&im, f64::to_le_bytes));
+        }
+        MatValue::Char(s) => {
+            let data = s.encode_utf16().collect::<Vec<u16>>();
+            write_element(&mut body, MI_UINT16, &le_bytes(&data, u16::to_le_bytes))
+        }
+        MatValue::Struct(fields) => {
+            let length = MAX_NAME_LENGTH + 1;
+            write_element(&mut body, MI_INT32, &(length as i32).to_le_bytes());
+            let mut names = Vec::new();
+            for (name, _) in fields.iter() {
+                let mut name = name.as_bytes().to_vec();
+                name.resize(length, 0);
+                names.extend(name);
+            }
+            write_element(&mut body, MI_INT8, &names);
+            for (_, value) in fields.iter() {
+                body.extend(encode_matrix("", value));
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    write_element(&mut out, MI_MATRIX, &body);
+    out
+}
+
+fn le_bytes<T: Copy, const N: usize>(values: &[T], to_bytes: fn(T) -> [u8; N]) -> Vec<u8> {
+    values.iter().flat_map(|&v| to_bytes(v)).collect()
+}
+
+/// Parse all top-level variables of a MAT v5 file.
+fn read_mat(bytes: &[u8]) -> Result<Vec<(String, MatValue)>, Box<dyn Error>> {
+    if bytes.len() < 128 || !bytes.starts_with(b"MATLAB 5.0 MAT-file") {
+        return Err("not a MATLAB 5.0 MAT-file".into());
+    }
+    if &bytes[126..128] != b"IM" {
+        return Err("only little-endian MAT-files are supported".into());
+    }
+    let mut variables = Vec::new();
+    read_elements(&bytes[128..], &mut variables)?;
+    Ok(variables)
+}
+
+fn read_elements(
+    bytes: &[u8],
+    variables: &mut Vec<(String, MatValue)>,
+) -> Result<(), Box<dyn Error>> {
+    let mut elements = Elements::new(bytes);
+    while let Some((mi_type, data)) = elements.next_element()? {
+        match mi_type {
+            MI_MATRIX => {
+                if let (name, Some(value)) = parse_matrix(data)? {
+                    variables.push((name, value));
+                }
+            }
+            MI_COMPRESSED => {
+                let mut inflated = Vec::new();
+                ZlibDecoder::new(data).read_to_end(&mut inflated)?;
+                read_elements(&inflated, variables)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Type and data of one data element.
+type Element<'a> = (u32, &'a [u8]);
+
+/// Iterator over the data elements of a byte buffer.
+struct Elements<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Elements<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn next_element(&mut self) -> Result<Option<Element<'a>>, Box<dyn Error>> {
+        if self.pos + 8 > self.bytes.len() {
+            return Ok(None);
+        }
+        let word = u32::from_le_bytes(self.bytes[self.pos..self.pos + 4].try_into()?);
+        // small data element format, up to four bytes are packed into the tag
+        if word >> 16 != 0 {
+            let length = (word >> 16) as usize;
+            if length > 4 {
+                return Err("corrupt small data element".into());
+            }
+            let data = &self.bytes[self.pos + 4..self.pos + 4 + length];
+            self.pos += 8;
+            return Ok(Some((word & 0xffff, data)));
+        }
+
+        let length =
+            u32::from_le_bytes(self.bytes[self.pos + 4..self.pos + 8].try_into()?) as usize;
+        let start = self.pos + 8;
+        let end = start + length;
+        if end > self.bytes.len() {
+            return Err("data element exceeds the end of the file".into());
+        }
+        // compressed elements are not padded
+        self.pos = if word == MI_COMPRESSED {
+            end
+        } else {
+            start + length.next_multiple_of(8)
+        };
+        Ok(Some((word, &self.bytes[start..end])))
+    }
+
+    fn expect(&mut self, what: &str) -> Result<(u32, &'a [u8]), Box<dyn Error>> {
+        self.next_element()?
+            .ok_or_else(|| format!("missing {} in array", what).into())
+    }
+}
+
+/// Parse a matrix element, the value is `None` for array classes that are not supported.
+fn parse_matrix(bytes: &[u8]) -> Result<(String, Option<MatValue>), Box<dyn Error>> {
+    // empty placeholder matrices carry no sub-elements at all
+    if bytes.is_empty() {
+        return Ok((String::new(), None));
+    }
+    let mut elements = Elements::new(bytes);
+    let (_, flags) = elements.expect("array flags")?;
+    let flags = u32::from_le_bytes(flags.get(..4).ok_or("corrupt array flags")?.try_into()?);
+    let class = (flags & 0xff) as u8;
+    let (dims_type, dims) = elements.expect("dimensions")?;
+    let dims = to_f64(dims_type, dims)?
+        .into_iter()
+        .map(|d| d as usize)
+        .collect::<Vec<usize>>();
+    let (_, name) = elements.expect("array name")?;
+    let name = String::from_utf8_lossy(name).to_string();
+
+    let value = match class {
+        MX_STRUCT_CLASS => {
+            if dims.iter().product::<usize>() != 1 {
+                None
+            } else {
+                let (length_type, length) = elements.expect("field name length")?;
+                let length = to_f64(length_type, length)?
+                    .first()
+                    .copied()
+                    .ok_or("corrupt field name length")? as usize;
+                let (_, names) = elements.expect("field names")?;
+                let mut fields = Vec::new();
+                for name in names.chunks(length.max(1)) {
+                    let name = name.split(|&b| b == 0).next().unwrap_or_default();
+                    if let (_, Some(value)) = parse_matrix(elements.expect("field")?.1)? {
+                        fields.push((String::from_utf8_lossy(name).to_string(), value));
+                    }
+                }
+                Some(MatValue::Struct(fields))
+            }
+        }
+        MX_CHAR_CLASS => {
+            let text = match elements.next_element()? {
+                Some((MI_UINT16 | MI_UTF16, data)) => String::from_utf16_lossy(
+                    &data
+                        .chunks_exact(2)
+                        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                        .collect::<Vec<u16>>(),
+                ),
+                Some((_, data)) => String::from_utf8_lossy(data).to_string(),
+                None => String::new(),
+            };
+            Some(MatValue::Char(text))
+        }
+        MX_DOUBLE_CLASS..=MX_UINT64_CLASS if flags & COMPLEX_FLAG != 0 => {
+            let (re_type, re) = elements.expect("real part")?;
+            let (im_type, im) = elements.expect("imaginary part")?;
+            let values = to_f64(re_type, re)?
+                .into_iter()
+                .zip(to_f64(im_type, im)?)
+                .map(|(re, im)| Complex::new(re, im))
+                .collect::<Vec<Complex<f64>>>();
+            let data = if (class, re_type, im_type) == (MX_SINGLE_CLASS, MI_SINGLE, MI_SINGLE) {
+                TypedArray::C32(from_column_major(
+                    &dims,
+                    values
+                        .iter()
+                        .map(|c| Complex::new(c.re as f32, c.im as f32))
+                        .collect(),
+                )?)
+            } else {
+                TypedArray::C64(from_column_major(&dims, values)?)
+            };
+            Some(MatValue::Numeric(data))
+        }
+        MX_DOUBLE_CLASS..=MX_UINT64_CLASS => {
+            let (data_type, data) = elements.expect("real part")?;
+            // MATLAB may store values in a smaller type than the array class
+            let values = to_f64(data_type, data)?;
+            let data = match (class, data_type) {
+                (MX_SINGLE_CLASS, MI_SINGLE) => TypedArray::F32(from_column_major(
+                    &dims,
+                    data.chunks_exact(4)
+                        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                        .collect(),
+                )?),
+                (MX_INT32_CLASS, _) => TypedArray::I32(from_column_major(
+                    &dims,
+                    values.iter().map(|&v| v as i32).collect(),
+                )?),
+                (MX_INT64_CLASS, MI_INT64) => TypedArray::I64(from_column_major(
+                    &dims,
+                    data.chunks_exact(8)
+                        .map(|c| i64::from_le_bytes(c.try_into().unwrap()))
+                        .collect(),
+                )?),
+                (MX_INT64_CLASS, _) => TypedArray::I64(from_column_major(
+                    &dims,
+                    values.iter().map(|&v| v as i64).collect(),
+                )?),
+                _ => TypedArray::F64(from_column_major(&dims, values)?),
+            };
+            Some(MatValue::Numeric(data))
+        }
+        _ => None,
+    };
+    Ok((name, value))
+}
+
+/// Convert numeric element data of any type to `f64`.
+fn to_f64(mi_type: u32, data: &[u8]) -> Result<Vec<f64>, Box<dyn Error>> {
+    macro_rules! convert {
+        ($t:ty) => {
+            data.chunks_exact(std::mem::size_of::<$t>())
+                .map(|c| <$t>::from_le_bytes(c.try_into().unwrap()) as f64)
+                .collect()
+        };
+    }
+    Ok(match mi_type {
+        MI_INT8 => convert!(i8),
+        MI_UINT8 | MI_UTF8 => convert!(u8),
+        MI_INT16 => convert!(i16),
+        MI_UINT16 => convert!(u16),
+        MI_INT32 => convert!(i32),
+        MI_UINT32 => convert!(u32),
+        MI_SINGLE => convert!(f32),
+        MI_DOUBLE => convert!(f64),
+        MI_INT64 => convert!(i64),
+        MI_UINT64 => convert!(u64),
+        _ => return Err(format!("unsupported numeric data type {}", mi_type).into()),
+    })
+}
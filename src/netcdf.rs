@@ -0,0 +1,288 @@
+use crate::attributes::write_string_attr;
+use crate::dotthz::{ds_index, is_measurement_group};
+use crate::scales::mark_dimension_scale;
+use crate::{is_dimension_scale, DatasetAttributes, DotthzFile};
+use hdf5::Group;
+use ndarray::{s, Array1};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::Path;
+
+/// Conventions announced in the root group.
+const CONVENTIONS: &str = "CF-1.8";
+
+/// `NAME` of a dimension scale that only defines a dimension, as written by the netCDF library.
+const PURE_DIMENSION: &str = "This is a netCDF dimension but not a netCDF variable.";
+
+/// Attributes holding object references of the source file, rewritten for the exported file.
+const REFERENCE_ATTRIBUTES: [&str; 2] = ["DIMENSION_LIST", "REFERENCE_LIST"];
+
+/// Attribute of a dimension scale holding its netCDF dimension ID, unique within the file.
+const DIMENSION_ID: &str = "_Netcdf4Dimid";
+
+/// Units of a time axis, see [`is_time_trace`].
+const TIME_UNITS: [&str; 7] = ["fs", "ps", "ns", "us", "µs", "ms", "s"];
+
+/// Write a copy of `file` meant to be opened with `xarray.open_dataset(path, group=...)`.
+///
+/// Groups, including nested ones, their attributes and the `dsN` datasets are copied unchanged,
+/// so the copy stays a valid dotThz file. Axes added with [`DotthzFile::add_axis`] stay
+/// dimension scales and remain attached to their datasets, they become coordinates. In
+/// addition, every other dimension of every dataset gets a netCDF4 dimension:
+///
+/// * the last dimension of a time-domain trace is `time`, a coordinate holding the time row, or
+///   `time_dsN` if the traces of a group do not share their time axis,
+/// * the first dimension of a trace is `row`, or `rowN` if traces have different row counts,
+/// * dimension `i` of any other dataset is `<dataset>_dimi`, e.g. `ds3_dim0`.
+///
+/// A `(n, N)` dataset is a time-domain trace if the unit or label of its first row is a time, or,
+/// without units and labels, if it is a `dsN` dataset described in `dsDescription` and no axis
+/// is attached to its first dimension. So the `(5, N)` noise parameters of a Touchstone import,
+/// which start with a frequency row, only get plain dimensions.
+///
+/// Datasets get the CF attributes `long_name` from `dsDescription` and `units` from the unit of
+/// their signal row, see [`DotthzFile::set_dataset_attributes`]. Every dimension scale gets the
+/// unique `_Netcdf4Dimid` the netCDF library assigns. `_NCProperties` is not written, as it
+/// records the netCDF library version that wrote a file; the netCDF library reads files without
+/// it as HDF5 files written by another library. Groups without attributes that only hold other
+/// groups are copied without dotThz attributes.
+///
+/// The layout follows the netCDF4 and CF conventions, opening the copy with the netCDF library
+/// or xarray has not been verified yet.
+pub fn export_netcdf<P: AsRef<Path>>(
+    file: &DotthzFile,
+    path: P,
+) -> Result<DotthzFile, Box<dyn Error>> {
+    let mut out = DotthzFile::create(&path.as_ref().to_path_buf())?;
+    let root = file.get_group("/")?;
+    export_groups(file, &mut out, &root)?;
+    let root = out.get_group("/")?;
+    write_string_attr(&root, "Conventions", CONVENTIONS)?;
+    number_dimensions(&root, &mut 0)?;
+    Ok(out)
+}
+
+/// Write `_Netcdf4Dimid` to the dimension scales of `group` and its subgroups, parents first.
+fn number_dimensions(group: &Group, next: &mut i32) -> Result<(), Box<dyn Error>> {
+    for dataset in group.datasets()? {
+        if !is_dimension_scale(&dataset) {
+            continue;
+        }
+        if dataset.attr(DIMENSION_ID).is_ok() {
+            dataset.delete_attr(DIMENSION_ID)?;
+        }
+        dataset
+            .new_attr::<i32>()
+            .create(DIMENSION_ID)?
+            .write_scalar(next)?;
+        *next += 1;
+    }
+    for subgroup in group.groups()? {
+        number_dimensions(&subgroup, next)?;
+    }
+    Ok(())
+}
+
+/// Export the subgroups of `parent` and their subgroups, parents first.
+fn export_groups(
+    file: &DotthzFile,
+    out: &mut DotthzFile,
+    parent: &Group,
+) -> Result<(), Box<dyn Error>> {
+    for group in parent.groups()? {
+        let group_name = group.name();
+        let group_name = group_name.trim_start_matches('/');
+        if group.attr_names()?.is_empty() && !is_measurement_group(&group, &group.groups()?)? {
+            out.get_group("/")?.create_group(group_name)?;
+        } else {
+            out.add_group(group_name, &file.get_meta_data(group_name)?)?;
+        }
+        export_group(file, out, group_name)?;
+        export_groups(file, out, &group)?;
+    }
+    Ok(())
+}
+
+fn export_group(
+    file: &DotthzFile,
+    out: &mut DotthzFile,
+    group_name: &str,
+) -> Result<(), Box<dyn Error>> {
+    let meta_data = file.get_meta_data(group_name)?;
+    let source = file.get_group(group_name)?.name();
+    let target = out.get_group(group_name)?;
+
+    // scales keep `CLASS` and `NAME`, the references are attached again in the copy
+    let mut datasets = Vec::new();
+    for dataset in file.get_datasets(group_name)? {
+        let name = dataset.name();
+        let name = name.rsplit('/').next().unwrap_or(&name).to_string();
+        dataset.copy_to(&target, &name)?;
+        let copy = target.dataset(&name)?;
+        for attr in REFERENCE_ATTRIBUTES {
+            if copy.attr(attr).is_ok() {
+                copy.delete_attr(attr)?;
+            }
+        }
+        if is_dimension_scale(&dataset) {
+            continue;
+        }
+        // only axes of the same group can be attached in the copy
+        let mut axes = Vec::new();
+        for dim in 0..dataset.ndim() {
+            axes.push(
+                file.get_axis(group_name, &name, dim)?
+                    .map(|axis| axis.name())
+                    .and_then(|axis| {
+                        axis.strip_prefix(&format!("{}/", source))
+                            .filter(|axis| !axis.contains('/'))
+                            .map(String::from)
+                    }),
+            );
+        }
+        datasets.push((name, dataset.shape(), axes));
+    }
+    for (name, _, axes) in datasets.iter() {
+        for (dim, axis) in axes.iter().enumerate() {
+            if let Some(axis) = axis {
+                out.attach_axis(group_name, name, dim, axis)?;
+            }
+        }
+    }
+    let is_copied = |name: &str| target.dataset(name).is_ok();
+
+    // time-domain traces hold the time axis in their first row
+    let mut traces = Vec::new();
+    for (name, shape, axes) in datasets.iter() {
+        let axis_attributes = match &axes[..] {
+            [_, Some(axis)] => Some(file.get_dataset_attributes(group_name, axis)?),
+            _ => None,
+        };
+        if is_time_trace(
+            name,
+            shape,
+            axes,
+            &file.get_dataset_attributes(group_name, name)?,
+            axis_attributes.as_ref(),
+            meta_data.dataset_description(name).is_some(),
+        ) {
+            let time = file
+                .get_dataset(group_name, name)?
+                .read_slice_1d::<f64, _>(s![0, ..])?;
+            traces.push((name.as_str(), shape[0], time, axes));
+        }
+    }
+    let new_time = traces
+        .iter()
+        .filter(|(_, _, _, axes)| axes[1].is_none())
+        .collect::<Vec<_>>();
+    let shared_time = !is_copied("time") && new_time.windows(2).all(|w| w[0].2 == w[1].2);
+    let row_counts = traces
+        .iter()
+        .filter(|(_, _, _, axes)| axes[0].is_none())
+        .map(|(_, rows, _, _)| *rows)
+        .collect::<BTreeSet<usize>>();
+    let shared_rows = row_counts.len() == 1 && !is_copied("row");
+
+    for &(name, rows, ref time, axes) in traces.iter() {
+        let attributes = file.get_dataset_attributes(group_name, name)?;
+        if axes[1].is_none() {
+            let time_name = if shared_time {
+                "time".to_string()
+            } else {
+                format!("time_{}", name)
+            };
+            if target.dataset(&time_name).is_err() {
+                out.add_dataset(group_name, &time_name, time.view())?;
+                let coordinate = target.dataset(&time_name)?;
+                mark_dimension_scale(&coordinate, &time_name)?;
+                let long_name = attributes.label(0).unwrap_or("Time");
+                write_string_attr(&coordinate, "long_name", long_name)?;
+                if let Some(unit) = attributes.unit(0) {
+                    write_string_attr(&coordinate, "units", unit)?;
+                }
+                write_string_attr(&coordinate, "axis", "T")?;
+            }
+            out.attach_axis(group_name, name, 1, &time_name)?;
+        }
+        if axes[0].is_none() {
+            let row_name = if shared_rows {
+                "row".to_string()
+            } else {
+                format!("row{}", rows)
+            };
+            add_dimension(out, &target, group_name, &row_name, rows)?;
+            out.attach_axis(group_name, name, 0, &row_name)?;
+        }
+        if let Some(unit) = attributes.unit(1) {
+            write_string_attr(&*target.dataset(name)?, "units", unit)?;
+        }
+    }
+
+    for (name, shape, axes) in datasets.iter() {
+        if !traces
+            .iter()
+            .any(|(trace, _, _, _)| *trace == name.as_str())
+        {
+            for (dim, axis) in axes.iter().enumerate() {
+                if axis.is_none() {
+                    let dimension_name = format!("{}_dim{}", name, dim);
+                    add_dimension(out, &target, group_name, &dimension_name, shape[dim])?;
+                    out.attach_axis(group_name, name, dim, &dimension_name)?;
+                }
+            }
+        }
+        let long_name = meta_data.dataset_description(name).unwrap_or(name);
+        write_string_attr(&*target.dataset(name)?, "long_name", long_name)?;
+    }
+    Ok(())
+}
+
+/// Whether a dataset is a time-domain trace of shape `(n, N)` whose first row is its time axis.
+///
+/// The unit or label of the first row decides, then those of an axis attached to the last
+/// dimension. Without either, the described `dsN` datasets of the dotThz standard are traces,
+/// unless an axis is attached to their first dimension, like complex spectra stored with a
+/// trailing dimension of 2.
+fn is_time_trace(
+    name: &str,
+    shape: &[usize],
+    axes: &[Option<String>],
+    attributes: &DatasetAttributes,
+    axis_attributes: Option<&DatasetAttributes>,
+    described: bool,
+) -> bool {
+    if shape.len() != 2 || shape[0] < 2 {
+        return false;
+    }
+    let time_row =
+        |attributes: &DatasetAttributes| match attributes.unit(0).filter(|unit| !unit.is_empty()) {
+            Some(unit) => Some(TIME_UNITS.contains(&unit)),
+            None => attributes
+                .label(0)
+                .filter(|label| !label.is_empty())
+                .map(|label| label.eq_ignore_ascii_case("time")),
+        };
+    time_row(attributes)
+        .or_else(|| axis_attributes.and_then(time_row))
+        .unwrap_or(ds_index(name).is_some() && described && axes[0].is_none())
+}
+
+/// Add a dimension without coordinate values, unless it exists already.
+fn add_dimension(
+    out: &mut DotthzFile,
+    target: &Group,
+    group_name: &str,
+    name: &str,
+    length: usize,
+) -> Result<(), Box<dyn Error>> {
+    if target.dataset(name).is_ok() {
+        return Ok(());
+    }
+    out.add_dataset(group_name, name, Array1::<f32>::zeros(length).view())?;
+    mark_dimension_scale(
+        &target.dataset(name)?,
+        &format!("{}{:>10}", PURE_DIMENSION, length),
+    )?;
+    Ok(())
+}
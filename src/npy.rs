@@ -0,0 +1,143 @@
+use crate::{load_meta_data, save_meta_data, DotthzFile, DotthzMetaData, TypedArray};
+use ndarray_npy::{read_npy, write_npy, NpzReader, NpzWriter};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Path of the JSON sidecar carrying the meta-data of a `.npy`/`.npz` file.
+pub fn meta_data_sidecar_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    path.as_ref().with_extension("json")
+}
+
+/// Write a dataset to a `.npy` file, keeping its element type for `f32`, `f64`, `i32` and `i64`.
+///
+/// Native complex datasets are written as `complex64` or `complex128`, other types are converted
+/// to `f64`.
+pub fn export_npy<P: AsRef<Path>>(
+    file: &DotthzFile,
+    group_name: &str,
+    dataset_name: &str,
+    path: P,
+) -> Result<(), Box<dyn Error>> {
+    let dataset = file.get_dataset(group_name, dataset_name)?;
+    match TypedArray::read(&dataset)? {
+        TypedArray::F32(data) => write_npy(path, &data)?,
+        TypedArray::F64(data) => write_npy(path, &data)?,
+        TypedArray::I32(data) => write_npy(path, &data)?,
+        TypedArray::I64(data) => write_npy(path, &data)?,
+        TypedArray::C32(data) => write_npy(path, &data)?,
+        TypedArray::C64(data) => write_npy(path, &data)?,
+    }
+    Ok(())
+}
+
+/// Write all datasets of a group to a `.npz` file, with the group meta-data, including the
+/// meta-data inherited from its parents, in a JSON sidecar.
+///
+/// Entries are named after the datasets, the sidecar is written next to `path` with the
+/// extension `.json` (see [`meta_data_sidecar_path`]).
+pub fn export_npz<P: AsRef<Path>>(
+    file: &DotthzFile,
+    group_name: &str,
+    path: P,
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut npz = NpzWriter::new_compressed(fs::File::create(path)?);
+    for dataset in file.get_datasets(group_name)? {
+        let name = dataset.name();
+        let name = name.rsplit('/').next().unwrap_or(&name).to_string();
+        match TypedArray::read(&dataset)? {
+            TypedArray::F32(data) => npz.add_array(name, &data)?,
+            TypedArray::F64(data) => npz.add_array(name, &data)?,
+            TypedArray::I32(data) => npz.add_array(name, &data)?,
+            TypedArray::I64(data) => npz.add_array(name, &data)?,
+            TypedArray::C32(data) => npz.add_array(name, &data)?,
+            TypedArray::C64(data) => npz.add_array(name, &data)?,
+        }
+    }
+    npz.finish()?;
+    save_meta_data(
+        meta_data_sidecar_path(path),
+        &file.get_inherited_meta_data(group_name)?,
+    )
+}
+
+/// Read a `.npy` file into a new dataset of an existing group.
+pub fn import_npy<P: AsRef<Path>>(
+    file: &mut DotthzFile,
+    group_name: &str,
+    dataset_name: &str,
+    path: P,
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let data = if let Ok(data) = read_npy(path) {
+        TypedArray::F64(data)
+    } else if let Ok(data) = read_npy(path) {
+        TypedArray::F32(data)
+    } else if let Ok(data) = read_npy(path) {
+        TypedArray::C64(data)
+    } else if let Ok(data) = read_npy(path) {
+        TypedArray::C32(data)
+    } else if let Ok(data) = read_npy(path) {
+        TypedArray::I64(data)
+    } else {
+        TypedArray::I32(read_npy(path)?)
+    };
+    data.write_into(file, group_name, dataset_name)
+}
+
+/// Read a `.npz` file into a new group, one dataset per entry.
+///
+/// The meta-data is read from the JSON sidecar if it exists, otherwise the group gets default
+/// meta-data with a `dsDescription` listing the entry names. The group is removed again if an
+/// entry cannot be read or written, so a failed import leaves the file unchanged.
+pub fn import_npz<P: AsRef<Path>>(
+    file: &mut DotthzFile,
+    group_name: &str,
+    path: P,
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut npz = NpzReader::new(fs::File::open(path)?)?;
+    // entries are stored as `<name>.npy`, the dataset keeps the bare name
+    let entries = npz.names()?;
+    let names = entries
+        .iter()
+        .map(|entry| entry.trim_end_matches(".npy").to_string())
+        .collect::<Vec<String>>();
+
+    let sidecar = meta_data_sidecar_path(path);
+    let meta_data = if sidecar.exists() {
+        load_meta_data(sidecar)?
+    } else {
+        DotthzMetaData {
+            ds_description: names.clone(),
+            ..Default::default()
+        }
+    };
+    file.add_group(group_name, &meta_data)?;
+
+    let result = entries
+        .iter()
+        .zip(names.iter())
+        .try_for_each(|(entry, name)| {
+            let data = if let Ok(data) = npz.by_name(entry) {
+                TypedArray::F64(data)
+            } else if let Ok(data) = npz.by_name(entry) {
+                TypedArray::F32(data)
+            } else if let Ok(data) = npz.by_name(entry) {
+                TypedArray::C64(data)
+            } else if let Ok(data) = npz.by_name(entry) {
+                TypedArray::C32(data)
+            } else if let Ok(data) = npz.by_name(entry) {
+                TypedArray::I64(data)
+            } else {
+                TypedArray::I32(npz.by_name(entry)?)
+            };
+            data.write_into(file, group_name, name)
+        });
+    if result.is_err() {
+        // do not leave a half-written group behind
+        file.delete_group(group_name)?;
+    }
+    result
+}
@@ -0,0 +1,148 @@
+use hdf5::filters::Filter;
+#[cfg(feature = "hdf5-blosc")]
+use hdf5::filters::{Blosc, BloscShuffle};
+use std::error::Error;
+
+/// Target size of automatically chosen chunks in bytes.
+const AUTO_CHUNK_BYTES: usize = 1 << 20;
+
+/// Highest gzip and Blosc compression level.
+const MAX_LEVEL: u8 = 9;
+
+/// Storage options for new datasets, see [`DotthzFile::add_dataset_with`].
+///
+/// Any filter requires a chunked layout, if no chunk shape is given one is chosen with
+/// [`DatasetOptions::auto_chunk`]. The available filters depend on the enabled features, so
+/// options are built from [`DatasetOptions::default`] with the `with_*` methods.
+///
+/// [`DotthzFile::add_dataset_with`]: crate::DotthzFile::add_dataset_with
+#[derive(Default, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct DatasetOptions {
+    /// Chunk shape, must have as many dimensions as the dataset.
+    pub chunk: Option<Vec<usize>>,
+    /// gzip compression level from 0 to 9.
+    pub deflate: Option<u8>,
+    /// Apply the byte shuffle filter before compression.
+    pub shuffle: bool,
+    /// Add a Fletcher32 checksum to every chunk.
+    pub fletcher32: bool,
+    /// Compress with the LZF filter.
+    #[cfg(feature = "hdf5-lzf")]
+    pub lzf: bool,
+    /// Compress with Blosc, using the given codec, level (0 to 9) and shuffle mode.
+    #[cfg(feature = "hdf5-blosc")]
+    pub blosc: Option<(Blosc, u8, BloscShuffle)>,
+}
+
+impl DatasetOptions {
+    /// Byte shuffle followed by gzip compression at the given level.
+    pub fn compressed(level: u8) -> Self {
+        DatasetOptions::default().with_shuffle().with_deflate(level)
+    }
+
+    /// Store the dataset in chunks of the given shape.
+    pub fn with_chunk(mut self, chunk: &[usize]) -> Self {
+        self.chunk = Some(chunk.to_vec());
+        self
+    }
+
+    /// Compress with gzip at the given level from 0 to 9.
+    pub fn with_deflate(mut self, level: u8) -> Self {
+        self.deflate = Some(level);
+        self
+    }
+
+    /// Apply the byte shuffle filter before compression.
+    pub fn with_shuffle(mut self) -> Self {
+        self.shuffle = true;
+        self
+    }
+
+    /// Add a Fletcher32 checksum to every chunk.
+    pub fn with_fletcher32(mut self) -> Self {
+        self.fletcher32 = true;
+        self
+    }
+
+    /// Compress with the LZF filter.
+    #[cfg(feature = "hdf5-lzf")]
+    pub fn with_lzf(mut self) -> Self {
+        self.lzf = true;
+        self
+    }
+
+    /// Compress with Blosc, using the given codec, level from 0 to 9 and shuffle mode.
+    #[cfg(feature = "hdf5-blosc")]
+    pub fn with_blosc(mut self, codec: Blosc, level: u8, shuffle: BloscShuffle) -> Self {
+        self.blosc = Some((codec, level, shuffle));
+        self
+    }
+
+    /// Check the compression levels, which HDF5 would otherwise only reject on writing.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(level) = self.deflate.filter(|&level| level > MAX_LEVEL) {
+            return Err(format!(
+                "gzip compression level {} is out of range, expected 0 to {}",
+                level, MAX_LEVEL
+            )
+            .into());
+        }
+        #[cfg(feature = "hdf5-blosc")]
+        if let Some((_, level, _)) = self.blosc.filter(|&(_, level, _)| level > MAX_LEVEL) {
+            return Err(format!(
+                "Blosc compression level {} is out of range, expected 0 to {}",
+                level, MAX_LEVEL
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// The filters in the order they are applied.
+    pub fn filters(&self) -> Vec<Filter> {
+        let mut filters = Vec::new();
+        if self.shuffle {
+            filters.push(Filter::Shuffle);
+        }
+        if let Some(level) = self.deflate {
+            filters.push(Filter::Deflate(level));
+        }
+        #[cfg(feature = "hdf5-lzf")]
+        if self.lzf {
+            filters.push(Filter::LZF);
+        }
+        #[cfg(feature = "hdf5-blosc")]
+        if let Some((codec, level, shuffle)) = self.blosc {
+            filters.push(Filter::Blosc(codec, level, shuffle));
+        }
+        if self.fletcher32 {
+            filters.push(Filter::Fletcher32);
+        }
+        filters
+    }
+
+    /// Whether the dataset is stored in chunks, i.e. a chunk shape or any filter is set.
+    pub fn is_chunked(&self) -> bool {
+        self.chunk.is_some() || !self.filters().is_empty()
+    }
+
+    /// Chunk shape for a dataset of the given shape and element size.
+    ///
+    /// Chunks grow from the last dimension up to about 1 MiB, so the samples of a trace stay
+    /// together and whole traces or image rows are read with few chunk accesses.
+    pub fn auto_chunk(shape: &[usize], element_size: usize) -> Vec<usize> {
+        let max_elements = (AUTO_CHUNK_BYTES / element_size.max(1)).max(1);
+        let mut chunk = vec![1; shape.len()];
+        let mut elements = 1;
+        for (c, &dim) in chunk.iter_mut().zip(shape).rev() {
+            let dim = dim.max(1);
+            *c = dim.min(max_elements / elements).max(1);
+            elements *= *c;
+            if *c < dim {
+                break;
+            }
+        }
+        chunk
+    }
+}
@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Expand files and directories into a sorted, de-duplicated list of `.thz` files.
+///
+/// Directories are searched for `*.thz`, descending into sub-directories when `recursive` is
+/// set. Files are kept whatever their extension, paths that do not exist are an error.
+pub fn collect_thz_files<P: AsRef<Path>>(
+    paths: &[P],
+    recursive: bool,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            collect_dir(path, recursive, &mut files)?;
+        } else if path.exists() {
+            files.push(path.to_path_buf());
+        } else {
+            return Err(format!("`{}` does not exist", path.display()).into());
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn collect_dir(
+    dir: &Path,
+    recursive: bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                collect_dir(&path, recursive, files)?;
+            }
+        } else if is_thz(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_thz(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("thz"))
+}
@@ -0,0 +1,333 @@
+use crate::dotthz::ds_index;
+use crate::DotthzFile;
+use hdf5::types::{FixedAscii, VarLenArray, VarLenUnicode};
+use hdf5::{Dataset, H5Type, LocationToken, ObjectReference1, ReferencedObject};
+use ndarray::{s, Array1, ArrayView1};
+use std::error::Error;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// Value of the `CLASS` attribute marking a dimension scale, as written by the HDF5 library.
+///
+/// The attributes follow the layout of the HDF5 dimension scale API (H5DS), but are written by
+/// hand, so the high-level library is not needed; the tests check them with H5DS.
+const DIMENSION_SCALE: &str = "DIMENSION_SCALE";
+
+/// Entry of the `REFERENCE_LIST` attribute of a dimension scale, laid out as by the HDF5 library.
+#[derive(H5Type, Clone, Copy)]
+#[repr(C)]
+struct DimensionReference {
+    dataset: ObjectReference1,
+    dimension: i32,
+}
+
+impl DotthzFile {
+    /// Add a 1D dataset holding an axis, e.g. the time or frequency axis shared by several traces,
+    /// and mark it as HDF5 dimension scale.
+    ///
+    /// The axis is stored under `axis_name`, which must not be a `dsN` name: axes are not listed
+    /// in `dsDescription` and leave the numbering of the traces untouched. Attach it to the
+    /// datasets it belongs to with [`DotthzFile::attach_axis`] or
+    /// [`DotthzFile::attach_axis_to_group`].
+    pub fn add_axis<T: H5Type + Debug>(
+        &mut self,
+        group_name: &str,
+        axis_name: &str,
+        values: ArrayView1<'_, T>,
+    ) -> Result<(), Box<dyn Error>> {
+        if ds_index(axis_name).is_some() {
+            return Err(format!(
+                "axis `{}` must not be named like the `dsN` datasets described in `dsDescription`",
+                axis_name
+            )
+            .into());
+        }
+        self.add_dataset(group_name, axis_name, values)?;
+        mark_dimension_scale(&self.get_dataset(group_name, axis_name)?, axis_name)
+    }
+
+    /// Attach an axis added with [`DotthzFile::add_axis`] to dimension `dim` of a dataset.
+    ///
+    /// The dataset itself is left unchanged, so a `(2, N)` trace keeps its time row and stays
+    /// readable without dimension scale support.
+    pub fn attach_axis(
+        &mut self,
+        group_name: &str,
+        dataset_name: &str,
+        dim: usize,
+        axis_name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let group = self.get_group(group_name)?;
+        let dataset = group.dataset(dataset_name)?;
+        let axis = group.dataset(axis_name)?;
+        if !is_dimension_scale(&axis) {
+            return Err(format!("dataset `{}` is not a dimension scale", axis_name).into());
+        }
+        if dim >= dataset.ndim() {
+            return Err(format!(
+                "dataset `{}` has {} dimensions, cannot attach an axis to dimension {}",
+                dataset_name,
+                dataset.ndim(),
+                dim
+            )
+            .into());
+        }
+        if axis.ndim() != 1 || axis.size() != dataset.shape()[dim] {
+            return Err(format!(
+                "axis `{}` of shape {:?} does not match dimension {} of dataset `{}` of shape {:?}",
+                axis_name,
+                axis.shape(),
+                dim,
+                dataset_name,
+                dataset.shape()
+            )
+            .into());
+        }
+        if self
+            .get_axis(group_name, dataset_name, dim)?
+            .map(|a| a.name())
+            == Some(axis.name())
+        {
+            return Ok(());
+        }
+
+        attach_scale(&dataset, dim, &axis)?;
+        Ok(())
+    }
+
+    /// Attach an axis to the last dimension of every dataset of the group it fits.
+    ///
+    /// Other dimension scales and datasets whose last dimension differs in length are skipped.
+    /// Returns the names of the datasets the axis was attached to.
+    pub fn attach_axis_to_group(
+        &mut self,
+        group_name: &str,
+        axis_name: &str,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let length = self.get_dataset(group_name, axis_name)?.size();
+        let mut attached = Vec::new();
+        for dataset in self.get_datasets(group_name)? {
+            let name = dataset.name();
+            let name = name.rsplit('/').next().unwrap_or(&name).to_string();
+            if is_dimension_scale(&dataset) || dataset.shape().last() != Some(&length) {
+                continue;
+            }
+            self.attach_axis(group_name, &name, dataset.ndim() - 1, axis_name)?;
+            attached.push(name);
+        }
+        Ok(attached)
+    }
+
+    /// Get the first axis attached to dimension `dim` of a dataset, if any.
+    pub fn get_axis(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+        dim: usize,
+    ) -> Result<Option<Dataset>, Box<dyn Error>> {
+        let group = self.get_group(group_name)?;
+        let dataset = group.dataset(dataset_name)?;
+        let reference = read_dimension_list(&dataset)?
+            .and_then(|list| list.get(dim).and_then(|refs| refs.first().copied()));
+        match reference.map(|r| group.dereference(&r)).transpose()? {
+            Some(ReferencedObject::Dataset(axis)) => Ok(Some(axis)),
+            Some(_) => Err(format!(
+                "dimension {} of dataset `{}` refers to an object that is not a dataset",
+                dim, dataset_name
+            )
+            .into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the axis of dimension `dim` of a dataset.
+    ///
+    /// An attached dimension scale takes precedence. Without one, the last dimension of a plain
+    /// dotThz `(n, N)` dataset with `n >= 2` uses the first row, which holds the time axis.
+    pub fn read_axis<T: H5Type>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+        dim: usize,
+    ) -> Result<Array1<T>, Box<dyn Error>> {
+        if let Some(axis) = self.get_axis(group_name, dataset_name, dim)? {
+            return Ok(axis.read_1d()?);
+        }
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        if dataset.ndim() == 2 && dim == 1 && dataset.shape()[0] >= 2 {
+            return Ok(dataset.read_slice_1d(s![0, ..])?);
+        }
+        Err(format!(
+            "dimension {} of dataset `{}` has no axis attached",
+            dim, dataset_name
+        )
+        .into())
+    }
+}
+
+/// Whether a dataset is marked as HDF5 dimension scale.
+pub fn is_dimension_scale(dataset: &Dataset) -> bool {
+    dataset
+        .attr("CLASS")
+        .and_then(|a| a.read_scalar::<FixedAscii<16>>())
+        .is_ok_and(|class| class.as_str() == DIMENSION_SCALE)
+}
+
+/// Mark a dataset as dimension scale with the given name.
+pub(crate) fn mark_dimension_scale(dataset: &Dataset, name: &str) -> Result<(), Box<dyn Error>> {
+    dataset
+        .new_attr::<FixedAscii<16>>()
+        .create("CLASS")?
+        .write_scalar(&FixedAscii::<16>::from_ascii(DIMENSION_SCALE)?)?;
+    dataset
+        .new_attr::<VarLenUnicode>()
+        .create("NAME")?
+        .write_scalar(&VarLenUnicode::from_str(name)?)?;
+    Ok(())
+}
+
+/// Attach `axis` to dimension `dim` of `dataset`, without checking that it fits.
+pub(crate) fn attach_scale(dataset: &Dataset, dim: usize, axis: &Dataset) -> hdf5::Result<()> {
+    let file = dataset.file()?;
+
+    // every dimension of the dataset lists the scales attached to it
+    let mut dimension_list =
+        read_dimension_list(dataset)?.unwrap_or_else(|| vec![Vec::new(); dataset.ndim()]);
+    dimension_list[dim].push(file.reference::<ObjectReference1>(&axis.name())?);
+    write_dimension_list(dataset, &dimension_list)?;
+
+    // and every scale lists the datasets it is attached to
+    let mut reference_list = read_reference_list(axis)?;
+    reference_list.push(DimensionReference {
+        dataset: file.reference::<ObjectReference1>(&dataset.name())?,
+        dimension: dim as i32,
+    });
+    write_reference_list(axis, &reference_list)
+}
+
+/// Scales attached to each dimension of a dataset, and the datasets and dimensions a scale is
+/// attached to.
+pub(crate) type Attachments = (Vec<Vec<Dataset>>, Vec<(Dataset, usize)>);
+
+/// Detach a dataset from the scales attached to it and, if it is a scale itself, from the
+/// datasets it is attached to, so no `REFERENCE_LIST` or `DIMENSION_LIST` entry is left pointing
+/// at it once it is deleted. Returns what was attached, to attach it again to a replacement.
+pub(crate) fn detach_scales(dataset: &Dataset) -> hdf5::Result<Attachments> {
+    let token = dataset.loc_info()?.token;
+    let mut axes = vec![Vec::new(); dataset.ndim()];
+    if let Some(dimension_list) = read_dimension_list(dataset)? {
+        for (dim, references) in dimension_list.iter().enumerate() {
+            for reference in references {
+                let ReferencedObject::Dataset(axis) = dataset.dereference(reference)? else {
+                    continue;
+                };
+                let mut reference_list = Vec::new();
+                for entry in read_reference_list(&axis)? {
+                    if entry.dimension != dim as i32 || !points_to(&axis, &entry.dataset, token)? {
+                        reference_list.push(entry);
+                    }
+                }
+                write_reference_list(&axis, &reference_list)?;
+                if let Some(axes) = axes.get_mut(dim) {
+                    axes.push(axis);
+                }
+            }
+        }
+        replace_attr(dataset, "DIMENSION_LIST")?;
+    }
+
+    let mut attached = Vec::new();
+    for entry in read_reference_list(dataset)? {
+        let ReferencedObject::Dataset(target) = dataset.dereference(&entry.dataset)? else {
+            continue;
+        };
+        let dim = entry.dimension as usize;
+        if let Some(mut dimension_list) = read_dimension_list(&target)? {
+            if let Some(references) = dimension_list.get_mut(dim) {
+                let mut kept = Vec::new();
+                for reference in references.iter() {
+                    if !points_to(&target, reference, token)? {
+                        kept.push(*reference);
+                    }
+                }
+                *references = kept;
+            }
+            write_dimension_list(&target, &dimension_list)?;
+        }
+        attached.push((target, dim));
+    }
+    replace_attr(dataset, "REFERENCE_LIST")?;
+    Ok((axes, attached))
+}
+
+/// Whether a reference, dereferenced from `location`, points at the object with `token`.
+fn points_to(
+    location: &Dataset,
+    reference: &ObjectReference1,
+    token: LocationToken,
+) -> hdf5::Result<bool> {
+    Ok(match location.dereference(reference)? {
+        ReferencedObject::Dataset(dataset) => dataset.loc_info()?.token == token,
+        _ => false,
+    })
+}
+
+/// Write `DIMENSION_LIST`, removing it once no scale is attached as the HDF5 library does.
+fn write_dimension_list(
+    dataset: &Dataset,
+    dimension_list: &[Vec<ObjectReference1>],
+) -> hdf5::Result<()> {
+    replace_attr(dataset, "DIMENSION_LIST")?;
+    if dimension_list.iter().all(Vec::is_empty) {
+        return Ok(());
+    }
+    let dimension_list = dimension_list
+        .iter()
+        .map(|references| VarLenArray::from_slice(references))
+        .collect::<Vec<VarLenArray<ObjectReference1>>>();
+    dataset
+        .new_attr_builder()
+        .with_data(dimension_list.as_slice())
+        .create("DIMENSION_LIST")?;
+    Ok(())
+}
+
+/// Entries of `REFERENCE_LIST`, empty if the scale is not attached to any dataset.
+fn read_reference_list(axis: &Dataset) -> hdf5::Result<Vec<DimensionReference>> {
+    match axis.attr("REFERENCE_LIST") {
+        Ok(attr) => attr.read_raw::<DimensionReference>(),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Write `REFERENCE_LIST`, removing it once the scale is not attached to any dataset.
+fn write_reference_list(axis: &Dataset, reference_list: &[DimensionReference]) -> hdf5::Result<()> {
+    replace_attr(axis, "REFERENCE_LIST")?;
+    if !reference_list.is_empty() {
+        axis.new_attr_builder()
+            .with_data(reference_list)
+            .create("REFERENCE_LIST")?;
+    }
+    Ok(())
+}
+
+/// References of the scales attached to each dimension, if any are attached.
+fn read_dimension_list(dataset: &Dataset) -> hdf5::Result<Option<Vec<Vec<ObjectReference1>>>> {
+    match dataset.attr("DIMENSION_LIST") {
+        Ok(attr) => Ok(Some(
+            attr.read_raw::<VarLenArray<ObjectReference1>>()?
+                .iter()
+                .map(|references| references.as_slice().to_vec())
+                .collect(),
+        )),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Delete an attribute so it can be written again with a different shape.
+fn replace_attr(dataset: &Dataset, name: &str) -> hdf5::Result<()> {
+    if dataset.attr(name).is_ok() {
+        dataset.delete_attr(name)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::DotthzMetaData;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Load meta-data from a JSON or TOML sidecar file, chosen by the file extension.
+///
+/// Fields missing from the sidecar are left at their default value.
+pub fn load_meta_data<P: AsRef<Path>>(path: P) -> Result<DotthzMetaData, Box<dyn Error>> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    match extension(path).as_deref() {
+        Some("json") => Ok(serde_json::from_str(&text)?),
+        Some("toml") => Ok(toml::from_str(&text)?),
+        _ => Err(format!(
+            "unsupported sidecar `{}`, expected a .json or .toml file",
+            path.display()
+        )
+        .into()),
+    }
+}
+
+/// Save meta-data to a JSON or TOML sidecar file, chosen by the file extension.
+pub fn save_meta_data<P: AsRef<Path>>(
+    path: P,
+    meta_data: &DotthzMetaData,
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let text = match extension(path).as_deref() {
+        Some("json") => serde_json::to_string_pretty(meta_data)?,
+        Some("toml") => toml::to_string_pretty(meta_data)?,
+        _ => {
+            return Err(format!(
+                "unsupported sidecar `{}`, expected a .json or .toml file",
+                path.display()
+            )
+            .into())
+        }
+    };
+    fs::write(path, text)?;
+    Ok(())
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
@@ -0,0 +1,82 @@
+use crate::DotthzFile;
+use hdf5::{H5Type, Hyperslab, Selection, SliceOrIndex};
+use ndarray::{s, Array, Array1, Array2};
+use std::error::Error;
+
+impl DotthzFile {
+    /// Read part of a dataset without loading the rest of it.
+    ///
+    /// The selection is given with ndarray's `s![]` macro, e.g. `s![.., 100..200]` for a time
+    /// window of all traces or `s![10..20, 10..20, ..]` for a block of pixels of an image cube.
+    pub fn read_slice<T, S, D>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+        selection: S,
+    ) -> Result<Array<T, D>, Box<dyn Error>>
+    where
+        T: H5Type,
+        S: TryInto<Selection>,
+        hdf5::Error: From<S::Error>,
+        D: ndarray::Dimension,
+    {
+        Ok(self
+            .get_dataset(group_name, dataset_name)?
+            .read_slice(selection)?)
+    }
+
+    /// Read a single trace along the last axis of a dataset.
+    ///
+    /// `index` holds one index per leading axis, e.g. `[1]` for the first signal of a `(2, N)`
+    /// dataset or `[x, y]` for a pixel of an `(x, y, t)` image cube.
+    pub fn read_trace<T: H5Type>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+        index: &[usize],
+    ) -> Result<Array1<T>, Box<dyn Error>> {
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        if index.len() + 1 != dataset.ndim() {
+            return Err(format!(
+                "dataset `{}` has {} dimensions, a trace needs {} indices",
+                dataset_name,
+                dataset.ndim(),
+                dataset.ndim().saturating_sub(1)
+            )
+            .into());
+        }
+        let hyperslab = index
+            .iter()
+            .map(|&i| SliceOrIndex::from(i))
+            .chain([SliceOrIndex::from(..)])
+            .collect::<Vec<SliceOrIndex>>();
+        Ok(dataset.read_slice_1d(Selection::from(Hyperslab::from(hyperslab)))?)
+    }
+
+    /// Read the samples of a `(n, N)` dataset whose time, in the first row, lies within
+    /// `start..=end`.
+    ///
+    /// All rows are returned, including the time axis. The time axis must be sorted ascending.
+    pub fn read_time_window<T>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+        start: T,
+        end: T,
+    ) -> Result<Array2<T>, Box<dyn Error>>
+    where
+        T: H5Type + PartialOrd,
+    {
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        if dataset.ndim() != 2 {
+            return Err(format!("dataset `{}` is not 2D", dataset_name).into());
+        }
+        let time = dataset.read_slice_1d::<T, _>(s![0, ..])?;
+        let first = time.iter().position(|t| *t >= start).unwrap_or(time.len());
+        let last = time.iter().rposition(|t| *t <= end).map_or(0, |i| i + 1);
+        if first >= last {
+            return Ok(Array2::from_shape_vec((dataset.shape()[0], 0), Vec::new())?);
+        }
+        Ok(dataset.read_slice_2d(s![.., first..last])?)
+    }
+}
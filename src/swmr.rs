@@ -0,0 +1,117 @@
+use crate::DotthzFile;
+use hdf5::{Dataset, H5Type};
+use ndarray::{s, Array2};
+use std::error::Error;
+use std::marker::PhantomData;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Follows a dataset that another process appends rows to, see [`DotthzFile::open_swmr`].
+///
+/// Each call to [`TraceWatcher::poll`] returns the rows appended since the previous call, the
+/// first call returns all rows written so far.
+pub struct TraceWatcher<T> {
+    dataset: Dataset,
+    rows: usize,
+    element: PhantomData<T>,
+}
+
+impl DotthzFile {
+    /// Watch a 2D dataset for appended rows.
+    pub fn watch_dataset<T: H5Type>(
+        &self,
+        group_name: &str,
+        dataset_name: &str,
+    ) -> Result<TraceWatcher<T>, Box<dyn Error>> {
+        let dataset = self.get_dataset(group_name, dataset_name)?;
+        if dataset.ndim() != 2 {
+            return Err(format!("dataset `{}` is not 2D", dataset_name).into());
+        }
+        Ok(TraceWatcher {
+            dataset,
+            rows: 0,
+            element: PhantomData,
+        })
+    }
+}
+
+impl<T: H5Type> TraceWatcher<T> {
+    /// Reload the extent of the dataset and return its current number of rows.
+    pub fn refresh(&mut self) -> hdf5::Result<usize> {
+        self.dataset.refresh()?;
+        Ok(self.dataset.shape()[0])
+    }
+
+    /// Return the rows appended since the last call, possibly none.
+    pub fn poll(&mut self) -> Result<Array2<T>, Box<dyn Error>> {
+        let end = self.refresh()?;
+        if end <= self.rows {
+            let columns = self.dataset.shape()[1];
+            return Ok(Array2::from_shape_vec((0, columns), Vec::new())?);
+        }
+        let rows = self.dataset.read_slice_2d::<T, _>(s![self.rows..end, ..])?;
+        self.rows = end;
+        Ok(rows)
+    }
+
+    /// Iterate over newly appended rows, checking every `interval`.
+    ///
+    /// The iteration ends once no rows were appended for `idle_timeout`, or after the first
+    /// error.
+    pub fn watch(self, interval: Duration, idle_timeout: Duration) -> Watch<T> {
+        Watch {
+            watcher: self,
+            interval,
+            idle_timeout,
+            failed: false,
+        }
+    }
+
+    /// Number of rows returned so far.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns `true` if no rows have been returned yet.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// The watched dataset.
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+}
+
+/// Iterator over appended rows, see [`TraceWatcher::watch`].
+pub struct Watch<T> {
+    watcher: TraceWatcher<T>,
+    interval: Duration,
+    idle_timeout: Duration,
+    failed: bool,
+}
+
+impl<T: H5Type> Iterator for Watch<T> {
+    type Item = Result<Array2<T>, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let start = Instant::now();
+        loop {
+            match self.watcher.poll() {
+                Ok(rows) if rows.is_empty() => {}
+                Ok(rows) => return Some(Ok(rows)),
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+            if start.elapsed() >= self.idle_timeout {
+                return None;
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
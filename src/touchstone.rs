@@ -0,0 +1,467 @@
+use crate::{DatasetAttributes, DotthzFile, DotthzMetaData};
+use ndarray::{Array2, ArrayView1, Ix1};
+use num_complex::Complex64;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// `md` key holding the reference impedance of imported network parameters.
+pub const REFERENCE_IMPEDANCE_KEY: &str = "Reference impedance (Ohm)";
+
+/// `md` key holding the type of imported network parameters, `S`, `Y`, `Z`, `H` or `G`.
+pub const PARAMETER_KEY: &str = "Network parameter";
+
+/// `dsDescription` entry of the dataset holding the noise parameters of a two-port.
+pub const NOISE_DESCRIPTION: &str = "Noise parameters";
+
+/// Name of the frequency axis shared by the spectra of a group.
+const FREQUENCY_AXIS: &str = "frequency";
+
+/// Parameter types of Touchstone files.
+const PARAMETERS: [char; 5] = ['S', 'Y', 'Z', 'H', 'G'];
+
+/// Maximum number of values written on one line, as required for Touchstone files.
+const VALUES_PER_LINE: usize = 4;
+
+/// Network parameters read from or written to a Touchstone (`.sNp`) file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Touchstone {
+    /// Number of ports.
+    pub ports: usize,
+    /// Parameter type, `'S'`, `'Y'`, `'Z'`, `'H'` or `'G'`.
+    pub parameter: char,
+    /// Reference impedance in Ohm.
+    pub reference_impedance: f64,
+    /// Frequency axis in Hz.
+    pub frequency: Vec<f64>,
+    /// One column of complex values per parameter, in the order of [`Touchstone::names`].
+    pub values: Vec<Vec<Complex64>>,
+    /// Noise parameters following the network parameters of two-port files.
+    pub noise: Vec<NoiseParameters>,
+}
+
+/// Noise parameters of a two-port at one frequency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseParameters {
+    /// Frequency in Hz.
+    pub frequency: f64,
+    /// Minimum noise figure in dB.
+    pub min_noise_figure: f64,
+    /// Source reflection coefficient giving the minimum noise figure.
+    pub optimal_reflection: Complex64,
+    /// Effective noise resistance, normalized to the reference impedance.
+    pub noise_resistance: f64,
+}
+
+impl Touchstone {
+    /// Names of the parameters in file order, e.g. `S11`, `S21`, `S12`, `S22` for two ports.
+    ///
+    /// Two-port files list the parameters column-wise, all others row-wise.
+    pub fn names(&self) -> Vec<String> {
+        let n = self.ports;
+        (0..n * n)
+            .map(|k| {
+                let (i, j) = if n == 2 {
+                    (k % n, k / n)
+                } else {
+                    (k / n, k % n)
+                };
+                format!("{}{}{}", self.parameter, i + 1, j + 1)
+            })
+            .collect()
+    }
+}
+
+/// Store a complex spectrum with [`DotthzFile::add_complex_dataset`] and attach its frequency
+/// axis (Hz) as dimension scale.
+///
+/// Spectra of a group share the axis `frequency`, a spectrum sampled at other frequencies gets
+/// an axis of its own named `frequency_<dataset_name>`.
+pub fn add_spectrum(
+    file: &mut DotthzFile,
+    group_name: &str,
+    dataset_name: &str,
+    frequency: &[f64],
+    values: &[Complex64],
+) -> Result<(), Box<dyn Error>> {
+    if frequency.len() != values.len() {
+        return Err(format!(
+            "{} frequencies given for {} values",
+            frequency.len(),
+            values.len()
+        )
+        .into());
+    }
+    let group = file.get_group(group_name)?;
+    let mut axis_name = FREQUENCY_AXIS.to_string();
+    if group.link_exists(&axis_name) && group.dataset(&axis_name)?.read_raw::<f64>()? != frequency {
+        axis_name = format!("{}_{}", FREQUENCY_AXIS, dataset_name);
+    }
+    if !group.link_exists(&axis_name) {
+        file.add_axis(group_name, &axis_name, ArrayView1::from(frequency))?;
+        let attributes = DatasetAttributes {
+            units: vec!["Hz".to_string()],
+            labels: vec!["Frequency".to_string()],
+            ..Default::default()
+        };
+        file.set_dataset_attributes(group_name, &axis_name, &attributes)?;
+    }
+    file.add_complex_dataset(group_name, dataset_name, ArrayView1::from(values))?;
+    file.attach_axis(group_name, dataset_name, 0, &axis_name)
+}
+
+/// Read a spectrum stored by [`add_spectrum`], returning frequency axis and complex values.
+pub fn read_spectrum(
+    file: &DotthzFile,
+    group_name: &str,
+    dataset_name: &str,
+) -> Result<(Vec<f64>, Vec<Complex64>), Box<dyn Error>> {
+    let values = file
+        .read_complex::<f64>(group_name, dataset_name)?
+        .into_dimensionality::<Ix1>()
+        .map_err(|_| format!("dataset `{}` is not a 1D spectrum", dataset_name))?;
+    let frequency = file.read_axis::<f64>(group_name, dataset_name, 0)?;
+    Ok((frequency.to_vec(), values.to_vec()))
+}
+
+/// Parse the contents of a Touchstone (version 1) file with the given number of ports.
+///
+/// Frequencies are converted to Hz, values from any of the `RI`, `MA` and `DB` formats to
+/// complex numbers. Two-port files may be followed by a block of noise parameters, starting at
+/// a frequency not above the last one of the network parameters.
+pub fn parse_touchstone(text: &str, ports: usize) -> Result<Touchstone, Box<dyn Error>> {
+    let mut unit = 1e9;
+    let mut parameter = 'S';
+    let mut format = "MA".to_string();
+    let mut reference_impedance = 50.0;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let line = line.split('!').next().unwrap_or("").trim();
+        if let Some(options) = line.strip_prefix('#') {
+            let tokens = options
+                .split_whitespace()
+                .map(str::to_uppercase)
+                .collect::<Vec<String>>();
+            let mut tokens = tokens.iter();
+            while let Some(token) = tokens.next() {
+                match token.as_str() {
+                    "HZ" => unit = 1.0,
+                    "KHZ" => unit = 1e3,
+                    "MHZ" => unit = 1e6,
+                    "GHZ" => unit = 1e9,
+                    "S" | "Y" | "Z" | "H" | "G" => parameter = token.chars().next().unwrap(),
+                    "RI" | "MA" | "DB" => format = token.clone(),
+                    "R" => {
+                        reference_impedance = tokens
+                            .next()
+                            .and_then(|r| r.parse().ok())
+                            .ok_or("missing reference impedance after `R`")?
+                    }
+                    _ => return Err(format!("unknown option `{}`", token).into()),
+                }
+            }
+        } else if line.starts_with('[') {
+            return Err("Touchstone 2.0 keywords are not supported".into());
+        } else if !line.is_empty() {
+            let numbers = line
+                .split_whitespace()
+                .map(|token| {
+                    token
+                        .parse::<f64>()
+                        .map_err(|_| format!("`{}` is not a number", token))
+                })
+                .collect::<Result<Vec<f64>, String>>()?;
+            lines.push(numbers);
+        }
+    }
+
+    // rows of more than two ports wrap over several lines
+    let width = 1 + 2 * ports * ports;
+    let mut numbers = Vec::new();
+    let mut noise_lines: &[Vec<f64>] = &[];
+    let mut last_frequency = None;
+    for (i, line) in lines.iter().enumerate() {
+        if numbers.len().is_multiple_of(width) {
+            if ports == 2 && line.len() == 5 && last_frequency.is_some_and(|f| line[0] <= f) {
+                noise_lines = &lines[i..];
+                break;
+            }
+            last_frequency = Some(line[0]);
+        }
+        numbers.extend_from_slice(line);
+    }
+    if !numbers.len().is_multiple_of(width) {
+        return Err(format!(
+            "{} values do not form rows of {} for {} ports",
+            numbers.len(),
+            width,
+            ports
+        )
+        .into());
+    }
+    let mut touchstone = Touchstone {
+        ports,
+        parameter,
+        reference_impedance,
+        frequency: Vec::with_capacity(numbers.len() / width),
+        values: vec![Vec::with_capacity(numbers.len() / width); ports * ports],
+        noise: Vec::with_capacity(noise_lines.len()),
+    };
+    for row in numbers.chunks(width) {
+        touchstone.frequency.push(row[0] * unit);
+        for (column, pair) in touchstone.values.iter_mut().zip(row[1..].chunks(2)) {
+            let (a, b) = (pair[0], pair[1]);
+            column.push(match format.as_str() {
+                "RI" => Complex64::new(a, b),
+                "DB" => Complex64::from_polar(10f64.powf(a / 20.0), b.to_radians()),
+                _ => Complex64::from_polar(a, b.to_radians()),
+            });
+        }
+    }
+    for line in noise_lines {
+        if line.len() != 5 {
+            return Err(format!("noise parameter rows have 5 values, found {}", line.len()).into());
+        }
+        touchstone.noise.push(NoiseParameters {
+            frequency: line[0] * unit,
+            min_noise_figure: line[1],
+            // the optimal reflection coefficient is always given as magnitude and angle
+            optimal_reflection: Complex64::from_polar(line[2], line[3].to_radians()),
+            noise_resistance: line[4],
+        });
+    }
+    Ok(touchstone)
+}
+
+/// Format network parameters as a Touchstone file with frequencies in Hz and `RI` values.
+///
+/// Files with more than two ports start each row of the parameter matrix on a new line, with at
+/// most four values per line. Noise parameters are written after the network parameters.
+pub fn format_touchstone(touchstone: &Touchstone) -> String {
+    let mut text = format!(
+        "# HZ {} RI R {}\n",
+        touchstone.parameter, touchstone.reference_impedance
+    );
+    let n = touchstone.ports;
+    for (i, frequency) in touchstone.frequency.iter().enumerate() {
+        let _ = write!(text, "{}", frequency);
+        for (k, column) in touchstone.values.iter().enumerate() {
+            if n > 2 && k > 0 && (k.is_multiple_of(n) || (k % n).is_multiple_of(VALUES_PER_LINE)) {
+                text.push('\n');
+            }
+            let _ = write!(text, " {} {}", column[i].re, column[i].im);
+        }
+        text.push('\n');
+    }
+    if !touchstone.noise.is_empty() {
+        text.push_str("! noise parameters\n");
+    }
+    for noise in &touchstone.noise {
+        let _ = writeln!(
+            text,
+            "{} {} {} {} {}",
+            noise.frequency,
+            noise.min_noise_figure,
+            noise.optimal_reflection.norm(),
+            noise.optimal_reflection.arg().to_degrees(),
+            noise.noise_resistance
+        );
+    }
+    text
+}
+
+/// Import a Touchstone `.sNp` file into a new group, one spectrum per parameter.
+///
+/// The number of ports is taken from the file extension. The spectra are stored with
+/// [`add_spectrum`] and described by their parameter name (`S11`, `S21`, ...), parameter type
+/// and reference impedance are stored as `md` entries. Noise parameters become a dataset of
+/// shape `(5, N)` with the columns of the file in its rows, described as [`NOISE_DESCRIPTION`].
+///
+/// The file is parsed completely before the group is created, and the group is removed again if
+/// writing a dataset fails, so a failed import leaves the file unchanged.
+pub fn import_touchstone<P: AsRef<Path>>(
+    file: &mut DotthzFile,
+    group_name: &str,
+    path: P,
+    meta_data: &DotthzMetaData,
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let ports = ports_from_extension(path)?;
+    let touchstone = parse_touchstone(&fs::read_to_string(path)?, ports)?;
+
+    let mut meta_data = meta_data.clone();
+    meta_data.ds_description = touchstone.names();
+    if !touchstone.noise.is_empty() {
+        meta_data.ds_description.push(NOISE_DESCRIPTION.to_string());
+    }
+    meta_data.md.insert(
+        REFERENCE_IMPEDANCE_KEY.to_string(),
+        touchstone.reference_impedance.to_string(),
+    );
+    meta_data
+        .md
+        .insert(PARAMETER_KEY.to_string(), touchstone.parameter.to_string());
+    file.add_group(group_name, &meta_data)?;
+    if let Err(e) = write_touchstone(file, group_name, &touchstone) {
+        // do not leave a half-written group behind
+        file.delete_group(group_name)?;
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Export the spectra of a group to a Touchstone `.sNp` file.
+///
+/// The parameter type is read from the `md` entry [`PARAMETER_KEY`] and defaults to `S`. The
+/// datasets are looked up by their description (`S11`, `S21`, ...), the number of ports is
+/// taken from the file extension. All spectra must share the same frequency axis. Two-port
+/// noise parameters are exported if the group has a dataset described as
+/// [`NOISE_DESCRIPTION`].
+pub fn export_touchstone<P: AsRef<Path>>(
+    file: &DotthzFile,
+    group_name: &str,
+    path: P,
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let meta_data = file.get_meta_data(group_name)?;
+    let parameter = match meta_data.md.get(PARAMETER_KEY).map(|p| p.trim()) {
+        None => 'S',
+        Some(p) => p
+            .parse::<char>()
+            .ok()
+            .filter(|p| PARAMETERS.contains(p))
+            .ok_or_else(|| format!("unknown network parameter `{}`", p))?,
+    };
+    let mut touchstone = Touchstone {
+        ports: ports_from_extension(path)?,
+        parameter,
+        reference_impedance: meta_data
+            .md
+            .get(REFERENCE_IMPEDANCE_KEY)
+            .and_then(|r| r.parse().ok())
+            .unwrap_or(50.0),
+        frequency: Vec::new(),
+        values: Vec::new(),
+        noise: Vec::new(),
+    };
+    for name in touchstone.names() {
+        let dataset_name = described_dataset_name(file, group_name, &name)?;
+        let (frequency, values) = read_spectrum(file, group_name, &dataset_name)?;
+        if touchstone.values.is_empty() {
+            touchstone.frequency = frequency;
+        } else if frequency != touchstone.frequency {
+            return Err(format!(
+                "`{}` does not share the frequency axis of `{}11`",
+                name, parameter
+            )
+            .into());
+        }
+        touchstone.values.push(values);
+    }
+
+    if touchstone.ports == 2
+        && meta_data
+            .ds_description
+            .iter()
+            .any(|d| d == NOISE_DESCRIPTION)
+    {
+        let dataset_name = described_dataset_name(file, group_name, NOISE_DESCRIPTION)?;
+        let data = file
+            .get_dataset(group_name, &dataset_name)?
+            .read_2d::<f64>()?;
+        if data.nrows() != 5 {
+            return Err(format!(
+                "noise parameters `{}` have {} rows, expected 5",
+                dataset_name,
+                data.nrows()
+            )
+            .into());
+        }
+        touchstone.noise = data
+            .columns()
+            .into_iter()
+            .map(|column| NoiseParameters {
+                frequency: column[0],
+                min_noise_figure: column[1],
+                optimal_reflection: Complex64::from_polar(column[2], column[3].to_radians()),
+                noise_resistance: column[4],
+            })
+            .collect();
+    }
+    fs::write(path, format_touchstone(&touchstone))?;
+    Ok(())
+}
+
+/// Write the spectra and noise parameters of a Touchstone file into an existing group.
+fn write_touchstone(
+    file: &mut DotthzFile,
+    group_name: &str,
+    touchstone: &Touchstone,
+) -> Result<(), Box<dyn Error>> {
+    for (n, values) in touchstone.values.iter().enumerate() {
+        let name = format!("ds{}", n + 1);
+        add_spectrum(file, group_name, &name, &touchstone.frequency, values)?;
+    }
+
+    if !touchstone.noise.is_empty() {
+        let mut data = Array2::<f64>::zeros((5, touchstone.noise.len()));
+        for (j, noise) in touchstone.noise.iter().enumerate() {
+            data[[0, j]] = noise.frequency;
+            data[[1, j]] = noise.min_noise_figure;
+            data[[2, j]] = noise.optimal_reflection.norm();
+            data[[3, j]] = noise.optimal_reflection.arg().to_degrees();
+            data[[4, j]] = noise.noise_resistance;
+        }
+        let name = format!("ds{}", touchstone.values.len() + 1);
+        file.add_dataset(group_name, &name, data.view())?;
+        let attributes = DatasetAttributes {
+            units: ["Hz", "dB", "", "deg", ""].map(String::from).to_vec(),
+            labels: [
+                "Frequency",
+                "Minimum noise figure",
+                "Optimal reflection magnitude",
+                "Optimal reflection angle",
+                "Normalized noise resistance",
+            ]
+            .map(String::from)
+            .to_vec(),
+            ..Default::default()
+        };
+        file.set_dataset_attributes(group_name, &name, &attributes)?;
+    }
+    Ok(())
+}
+
+/// Name of the dataset of a group with the given description.
+fn described_dataset_name(
+    file: &DotthzFile,
+    group_name: &str,
+    description: &str,
+) -> Result<String, Box<dyn Error>> {
+    let name = file
+        .get_dataset_by_description(group_name, description)?
+        .name();
+    Ok(name.rsplit('/').next().unwrap_or(&name).to_string())
+}
+
+/// Number of ports of a `.sNp` file.
+fn ports_from_extension(path: &Path) -> Result<usize, Box<dyn Error>> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .and_then(|ext| {
+            ext.strip_prefix('s')?
+                .strip_suffix('p')?
+                .parse::<usize>()
+                .ok()
+        })
+        .filter(|&ports| ports > 0)
+        .ok_or_else(|| {
+            format!(
+                "cannot tell the number of ports of `{}`, expected a .sNp file",
+                path.display()
+            )
+            .into()
+        })
+}
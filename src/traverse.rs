@@ -0,0 +1,145 @@
+use crate::dotthz::{ds_index, inherit_meta_data, is_measurement_group};
+use crate::{DotthzFile, DotthzGroup, DotthzMetaData};
+use hdf5::{Dataset, Group, LocationType};
+use std::error::Error;
+
+/// Lazy iterator over the measurement groups of a file, see [`DotthzFile::groups_iter`].
+///
+/// Yields the full path, the meta-data inherited from the parent groups (see
+/// [`DotthzFile::get_inherited_meta_data`]) and a handle of each group. Meta-data is only read
+/// when a group is reached, so stopping early or filtering by name avoids reading the rest.
+pub struct GroupsIter {
+    // groups still to visit with the meta-data inherited from their parents, the next one last
+    pending: Vec<(Group, DotthzMetaData)>,
+}
+
+impl Iterator for GroupsIter {
+    type Item = hdf5::Result<(String, DotthzMetaData, DotthzGroup)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((group, mut meta_data)) = self.pending.pop() {
+            let group = DotthzGroup::from(group);
+            let (subgroups, own) = match (group.groups(), group.meta_data()) {
+                (Ok(subgroups), Ok(own)) => (subgroups, own),
+                (Err(e), _) | (_, Err(e)) => return Some(Err(e)),
+            };
+            let ds_description = own.ds_description.clone();
+            inherit_meta_data(&mut meta_data, own);
+            let is_measurement = is_measurement_group(&group, &subgroups);
+            self.pending.extend(
+                subgroups
+                    .into_iter()
+                    .rev()
+                    .map(|subgroup| (subgroup, meta_data.clone())),
+            );
+            match is_measurement {
+                Ok(true) => {
+                    meta_data.ds_description = ds_description;
+                    return Some(Ok((group.name(), meta_data, group)));
+                }
+                Ok(false) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+/// Lazy iterator over the datasets of a group, see [`DotthzGroup::datasets_iter`].
+///
+/// Yields the name, the `dsDescription` entry and a handle of each dataset, `dsN` datasets
+/// ordered by `N`. Datasets are only opened when reached.
+pub struct DatasetsIter {
+    group: DotthzGroup,
+    meta_data: DotthzMetaData,
+    // dataset names still to visit, the next one last
+    pending: Vec<String>,
+}
+
+impl Iterator for DatasetsIter {
+    type Item = hdf5::Result<(String, Option<String>, Dataset)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.pending.pop()?;
+        let description = self.meta_data.dataset_description(&name).map(String::from);
+        Some(self.group.dataset(&name).map(|d| (name, description, d)))
+    }
+}
+
+/// Callbacks for walking a file with [`DotthzFile::visit`].
+///
+/// All methods have default implementations, so a visitor only implements what it needs.
+pub trait DotthzVisitor {
+    /// Called for every measurement group, before its datasets.
+    ///
+    /// Return `false` to skip the datasets of the group.
+    fn visit_group(
+        &mut self,
+        _name: &str,
+        _meta_data: &DotthzMetaData,
+        _group: &DotthzGroup,
+    ) -> Result<bool, Box<dyn Error>> {
+        Ok(true)
+    }
+
+    /// Called for every dataset of a group that was not skipped.
+    fn visit_dataset(
+        &mut self,
+        _group: &DotthzGroup,
+        _name: &str,
+        _description: Option<&str>,
+        _dataset: &Dataset,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl DotthzFile {
+    /// Iterate lazily over all measurement groups, including nested ones, in the order of
+    /// [`DotthzFile::get_measurement_groups`].
+    pub fn groups_iter(&self) -> hdf5::Result<GroupsIter> {
+        let root = self.get_meta_data("/")?;
+        let pending = self
+            .get_group("/")?
+            .groups()?
+            .into_iter()
+            .rev()
+            .map(|group| (group, root.clone()))
+            .collect();
+        Ok(GroupsIter { pending })
+    }
+
+    /// Walk all measurement groups and their datasets, stopping at the first error.
+    pub fn visit<V: DotthzVisitor>(&self, visitor: &mut V) -> Result<(), Box<dyn Error>> {
+        for group in self.groups_iter()? {
+            let (name, meta_data, group) = group?;
+            if !visitor.visit_group(&name, &meta_data, &group)? {
+                continue;
+            }
+            for dataset in group.datasets_iter()? {
+                let (name, description, dataset) = dataset?;
+                visitor.visit_dataset(&group, &name, description.as_deref(), &dataset)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DotthzGroup {
+    /// Iterate lazily over the datasets of the group, `dsN` datasets ordered by `N`.
+    pub fn datasets_iter(&self) -> hdf5::Result<DatasetsIter> {
+        let mut pending = Vec::new();
+        for name in self.member_names()? {
+            if self.loc_type_by_name(&name)? == LocationType::Dataset {
+                pending.push(name);
+            }
+        }
+        pending.sort_by_key(|name| (ds_index(name).unwrap_or(usize::MAX), name.clone()));
+        pending.reverse();
+        Ok(DatasetsIter {
+            group: self.clone(),
+            meta_data: self.meta_data()?,
+            pending,
+        })
+    }
+}
@@ -0,0 +1,375 @@
+use crate::dotthz::ds_index;
+use crate::{is_dimension_scale, DotthzFile};
+use hdf5::types::VarLenUnicode;
+use hdf5::Group;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// String attributes every dotThz measurement group is expected to carry.
+const REQUIRED_ATTRIBUTES: [&str; 7] = [
+    "description",
+    "date",
+    "time",
+    "mode",
+    "instrument",
+    "user",
+    "thzVer",
+];
+
+/// Severity of a validation finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The file deviates from common practice but can still be read.
+    Warning,
+    /// The file violates the dotThz standard.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single finding reported by the validator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How severe the finding is.
+    pub severity: Severity,
+    /// The group the finding refers to, `None` for file-level findings.
+    pub group: Option<String>,
+    /// Human readable description of the finding.
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(group: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            group: group.map(String::from),
+            message: message.into(),
+        }
+    }
+
+    fn warning(group: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            group: group.map(String::from),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.group {
+            Some(group) => write!(f, "{}: {}: {}", self.severity, group, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// The outcome of validating a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Path of the validated file.
+    pub path: PathBuf,
+    /// All findings, in the order they were encountered.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Returns true if at least one finding is an error.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+
+    /// Returns true if at least one finding is a warning.
+    pub fn has_warnings(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Warning)
+    }
+
+    /// Returns true if the file produced no findings at all.
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Validate the file at `path` against the dotThz standard.
+///
+/// Failing to open the file is reported as an error diagnostic rather than returned, so a batch
+/// of files can always be validated to completion.
+pub fn validate_file<P: AsRef<Path>>(path: P) -> ValidationReport {
+    let path = path.as_ref().to_path_buf();
+    let diagnostics = match DotthzFile::open(&path) {
+        Ok(file) => validate(&file).unwrap_or_else(|e| {
+            vec![Diagnostic::error(
+                None,
+                format!("failed to read file: {}", e),
+            )]
+        }),
+        Err(e) => vec![Diagnostic::error(
+            None,
+            format!("failed to open file: {}", e),
+        )],
+    };
+    ValidationReport { path, diagnostics }
+}
+
+/// Validate an opened `DotthzFile` and return all findings.
+pub fn validate(file: &DotthzFile) -> hdf5::Result<Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let groups = file.get_measurement_groups()?;
+    if groups.is_empty() {
+        diagnostics.push(Diagnostic::warning(
+            None,
+            "file contains no measurement groups",
+        ));
+    }
+    for group in groups.iter() {
+        let inherited = inherited_attr_names(file, &group.name())?;
+        validate_group(group, &inherited, &mut diagnostics)?;
+    }
+    Ok(diagnostics)
+}
+
+/// Attributes set on the groups above a nested group, which it inherits.
+fn inherited_attr_names(file: &DotthzFile, group_name: &str) -> hdf5::Result<Vec<String>> {
+    let mut attr_names = file.get_group("/")?.attr_names()?;
+    let components = group_name
+        .split('/')
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<&str>>();
+    for i in 1..components.len() {
+        attr_names.extend(file.get_group(&components[..i].join("/"))?.attr_names()?);
+    }
+    Ok(attr_names)
+}
+
+fn validate_group(
+    group: &Group,
+    inherited: &[String],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> hdf5::Result<()> {
+    let group_name = group.name();
+    let name = Some(group_name.as_str());
+    let attr_names = group.attr_names()?;
+
+    for attr in REQUIRED_ATTRIBUTES {
+        if !attr_names.iter().any(|a| a == attr) {
+            if inherited.iter().any(|a| a == attr) {
+                continue;
+            }
+            let severity = if attr == "thzVer" {
+                Severity::Error
+            } else {
+                Severity::Warning
+            };
+            diagnostics.push(Diagnostic {
+                severity,
+                group: Some(group_name.clone()),
+                message: format!("missing attribute `{}`", attr),
+            });
+        } else if read_string_attr(group, attr).is_none() {
+            diagnostics.push(Diagnostic::warning(
+                name,
+                format!("attribute `{}` is not a variable-length string", attr),
+            ));
+        }
+    }
+
+    if let Some(user) = read_string_attr(group, "user") {
+        if user.split('/').count() != 4 {
+            diagnostics.push(Diagnostic::warning(
+                name,
+                "attribute `user` should have the form `orcid/user/email/institution`",
+            ));
+        }
+    }
+
+    validate_datasets(group, &attr_names, diagnostics)?;
+    validate_md(group, &attr_names, diagnostics);
+    Ok(())
+}
+
+fn validate_datasets(
+    group: &Group,
+    attr_names: &[String],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> hdf5::Result<()> {
+    let group_name = group.name();
+    let name = Some(group_name.as_str());
+    // dimension scales under their own name, i.e. axes added with `add_axis()` or netCDF4
+    // dimensions, are not dotThz datasets and not described in `dsDescription`
+    let dataset_names = group
+        .datasets()?
+        .iter()
+        .map(|d| (short_name(&d.name()), is_dimension_scale(d)))
+        .filter(|(name, scale)| !scale || ds_index(name).is_some())
+        .map(|(name, _)| name)
+        .collect::<Vec<String>>();
+
+    for dataset in group.datasets()? {
+        if dataset.size() == 0 {
+            diagnostics.push(Diagnostic::warning(
+                name,
+                format!("dataset `{}` is empty", short_name(&dataset.name())),
+            ));
+        }
+    }
+
+    for dataset_name in dataset_names.iter() {
+        if ds_index(dataset_name).is_none() {
+            diagnostics.push(Diagnostic::warning(
+                name,
+                format!(
+                    "dataset `{}` does not follow the `dsN` naming convention",
+                    dataset_name
+                ),
+            ));
+        }
+    }
+
+    if !attr_names.iter().any(|a| a == "dsDescription") {
+        diagnostics.push(Diagnostic::error(name, "missing attribute `dsDescription`"));
+        return Ok(());
+    }
+
+    let descriptions = match read_list_attr(group, "dsDescription") {
+        Some(descriptions) => descriptions,
+        None => {
+            diagnostics.push(Diagnostic::error(
+                name,
+                "attribute `dsDescription` is not a variable-length string",
+            ));
+            return Ok(());
+        }
+    };
+
+    if descriptions.len() != dataset_names.len() {
+        diagnostics.push(Diagnostic::error(
+            name,
+            format!(
+                "`dsDescription` lists {} entries but the group holds {} datasets",
+                descriptions.len(),
+                dataset_names.len()
+            ),
+        ));
+    }
+
+    // entries are positional, the n-th entry describes `dsN`
+    for description in descriptions.iter() {
+        if let Some((dataset_name, _)) = description.split_once(':') {
+            if ds_index(dataset_name.trim()).is_some() {
+                diagnostics.push(Diagnostic::error(
+                    name,
+                    format!(
+                        "`dsDescription` entry `{}` is prefixed with a dataset name, entries are \
+                         listed in the order of the datasets instead",
+                        description
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_md(group: &Group, attr_names: &[String], diagnostics: &mut Vec<Diagnostic>) {
+    let group_name = group.name();
+    let name = Some(group_name.as_str());
+
+    let mut md_indices = attr_names
+        .iter()
+        .filter_map(|a| md_index(a))
+        .collect::<Vec<usize>>();
+    md_indices.sort_unstable();
+
+    let descriptions = if attr_names.iter().any(|a| a == "mdDescription") {
+        match read_list_attr(group, "mdDescription") {
+            Some(descriptions) => descriptions,
+            None => {
+                diagnostics.push(Diagnostic::error(
+                    name,
+                    "attribute `mdDescription` is not a variable-length string",
+                ));
+                return;
+            }
+        }
+    } else {
+        if !md_indices.is_empty() {
+            diagnostics.push(Diagnostic::error(
+                name,
+                "group has `mdN` attributes but no `mdDescription`",
+            ));
+        }
+        return;
+    };
+
+    for i in 1..=descriptions.len() {
+        if !md_indices.contains(&i) {
+            diagnostics.push(Diagnostic::error(
+                name,
+                format!(
+                    "`mdDescription` entry `{}` has no matching attribute `md{}`",
+                    descriptions[i - 1],
+                    i
+                ),
+            ));
+        }
+    }
+
+    for i in md_indices {
+        if i == 0 || i > descriptions.len() {
+            diagnostics.push(Diagnostic::error(
+                name,
+                format!(
+                    "attribute `md{}` has no entry in `mdDescription` ({} entries)",
+                    i,
+                    descriptions.len()
+                ),
+            ));
+        }
+    }
+}
+
+/// Read a string attribute, `None` if it is missing or not a variable-length string.
+fn read_string_attr(group: &Group, name: &str) -> Option<String> {
+    group
+        .attr(name)
+        .and_then(|a| a.read_raw::<VarLenUnicode>())
+        .ok()
+        .and_then(|v| v.first().map(|s| s.to_string()))
+}
+
+/// Read a comma separated list attribute such as `dsDescription` or `mdDescription`.
+fn read_list_attr(group: &Group, name: &str) -> Option<Vec<String>> {
+    let values = group
+        .attr(name)
+        .and_then(|a| a.read_raw::<VarLenUnicode>())
+        .ok()?;
+    Some(
+        values
+            .iter()
+            .flat_map(|s| s.split(", ").map(String::from).collect::<Vec<String>>())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    )
+}
+
+/// Strip the parent path from an HDF5 object name.
+fn short_name(name: &str) -> String {
+    name.rsplit('/').next().unwrap_or(name).to_string()
+}
+
+/// The index `N` of an attribute named `mdN`.
+fn md_index(name: &str) -> Option<usize> {
+    name.strip_prefix("md")?.parse().ok()
+}